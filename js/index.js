@@ -1 +1,5 @@
-import("../pkg/index.js").catch(console.error);
+import("../pkg/index.js")
+  .then(snek => {
+    window.snek = snek.run();
+  })
+  .catch(console.error);
@@ -0,0 +1,195 @@
+// runs seeded games headless and reports how they went, for balance testing.
+//
+//   cargo run --release --bin simulate -- --runs 1000 --player autopilot
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use snek::engine::{Action, EngineConfig, InputSource, KeyState, Replay, ReplayPlayer};
+use snek::snek::{autopilot, PillType, SnekGame, Tuning};
+use std::process::exit;
+
+const USAGE: &str = "\
+usage: simulate [options]
+  --runs N           number of games to run (default 1000)
+  --seed S           seed of the first game. game i uses seed S + i (default 0)
+  --player P         who plays each game (default autopilot)
+                       autopilot    heads for pills and the exit, avoiding the boundary
+                       random       turns at random
+                       straight     never turns
+                       replay:FILE  the input recorded in a replay, on every seed
+  --max-seconds M    give up on a game after M seconds of game time (default 600)
+  --tuning FILE      json overriding fields of the default tuning, e.g. {\"player\": {\"speed\": 80}}";
+
+struct Options {
+  runs: u64,
+  seed: u64,
+  player: String,
+  max_seconds: f64,
+  tuning: Option<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+  let mut options = Options { runs: 1000, seed: 0, player: "autopilot".to_string(), max_seconds: 600.0, tuning: None };
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+    match arg.as_str() {
+      "--runs" => options.runs = value()?.parse().map_err(|e| format!("bad --runs: {e}"))?,
+      "--seed" => options.seed = value()?.parse().map_err(|e| format!("bad --seed: {e}"))?,
+      "--player" => options.player = value()?.clone(),
+      "--max-seconds" => options.max_seconds = value()?.parse().map_err(|e| format!("bad --max-seconds: {e}"))?,
+      "--tuning" => options.tuning = Some(value()?.clone()),
+      _ => return Err(format!("unknown argument {arg}")),
+    }
+  }
+  if options.seed.checked_add(options.runs).is_none() {
+    return Err(format!("--seed {} with --runs {} goes past the largest seed", options.seed, options.runs));
+  }
+  Ok(options)
+}
+
+// produces the input for each step of one game
+trait Player {
+  fn input(&mut self, game: &SnekGame) -> KeyState;
+}
+
+struct Autopilot;
+
+impl Player for Autopilot {
+  fn input(&mut self, game: &SnekGame) -> KeyState {
+    autopilot(game)
+  }
+}
+
+// holds a random direction, changing it about once a second
+struct RandomPlayer {
+  rng: Pcg32,
+  action: Action,
+}
+
+impl Player for RandomPlayer {
+  fn input(&mut self, _game: &SnekGame) -> KeyState {
+    if self.rng.gen::<f64>() < 0.02 {
+      self.action = [Action::Up, Action::Down, Action::Left, Action::Right][self.rng.gen_range(0..4)];
+    }
+    let mut key_state = KeyState::new();
+    key_state.press(self.action);
+    key_state
+  }
+}
+
+struct Straight;
+
+impl Player for Straight {
+  fn input(&mut self, _game: &SnekGame) -> KeyState {
+    let mut key_state = KeyState::new();
+    key_state.press(Action::Up);
+    key_state
+  }
+}
+
+impl Player for ReplayPlayer {
+  fn input(&mut self, _game: &SnekGame) -> KeyState {
+    self.process()
+  }
+}
+
+// a fresh player for the game with the given seed
+fn player(name: &str, seed: u64) -> Result<Box<dyn Player>, String> {
+  match name {
+    "autopilot" => Ok(Box::new(Autopilot)),
+    "random" => Ok(Box::new(RandomPlayer { rng: Pcg32::seed_from_u64(seed), action: Action::Up })),
+    "straight" => Ok(Box::new(Straight)),
+    _ => {
+      let path = name.strip_prefix("replay:").ok_or(format!("unknown player {name}"))?;
+      let json = std::fs::read_to_string(path).map_err(|e| format!("can't read {path}: {e}"))?;
+      Ok(Box::new(ReplayPlayer::new(Replay::from_json(&json)?)))
+    }
+  }
+}
+
+// what's left of a game once it's over
+struct Outcome {
+  won: bool,
+  seconds: f64,
+  // size the boundary had grown to
+  boundary: (f64, f64),
+  pills_eaten: Vec<u32>,
+}
+
+fn simulate(seed: u64, player: &mut dyn Player, config: &EngineConfig, max_seconds: f64) -> Outcome {
+  let mut game = SnekGame::new(seed, config);
+  let max_steps = (max_seconds * config.tick_rate as f64) as u64;
+  for _ in 0..max_steps {
+    if game.is_over() { break; }
+    let key_state = player.input(&game);
+    game.update(&key_state);
+  }
+  let boundary = game.boundary();
+  Outcome {
+    won: game.won(),
+    seconds: game.score().elapsed(),
+    boundary: (boundary.width, boundary.height),
+    pills_eaten: PillType::ALL.iter().map(|pill_type| game.pills_eaten(*pill_type)).collect(),
+  }
+}
+
+// the overrides in the file at path, checked against the default tuning
+fn tuning(path: &str) -> serde_json::Value {
+  let overrides = std::fs::read_to_string(path)
+    .map_err(|e| format!("can't read {path}: {e}"))
+    .and_then(|json| serde_json::from_str(&json).map_err(|e| format!("invalid tuning: {e}")))
+    .and_then(|overrides| Tuning::with_overrides(&overrides).map(|_| overrides));
+  overrides.unwrap_or_else(|e| {
+    eprintln!("{e}");
+    exit(2);
+  })
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+  let (sum, n) = values.fold((0.0, 0), |(sum, n), v| (sum + v, n + 1));
+  if n == 0 { 0.0 } else { sum / n as f64 }
+}
+
+fn report(options: &Options, outcomes: &[Outcome]) {
+  let mut seconds: Vec<f64> = outcomes.iter().map(|o| o.seconds).collect();
+  seconds.sort_by(f64::total_cmp);
+  let median = seconds.get(seconds.len() / 2).copied().unwrap_or(0.0);
+  let wins = outcomes.iter().filter(|o| o.won).count();
+
+  println!("{} runs, seeds {}..{}, player {}", outcomes.len(), options.seed, options.seed + options.runs, options.player);
+  println!("win rate        {:.1}%", 100.0 * wins as f64 / outcomes.len().max(1) as f64);
+  println!("survival (s)    mean {:.1}   median {:.1}   max {:.1}",
+           mean(seconds.iter().copied()), median, seconds.last().copied().unwrap_or(0.0));
+  println!("boundary (px)   mean {:.0}x{:.0}   max {:.0}x{:.0}",
+           mean(outcomes.iter().map(|o| o.boundary.0)),
+           mean(outcomes.iter().map(|o| o.boundary.1)),
+           outcomes.iter().map(|o| o.boundary.0).fold(0.0, f64::max),
+           outcomes.iter().map(|o| o.boundary.1).fold(0.0, f64::max));
+  println!("pills per run");
+  for (i, pill_type) in PillType::ALL.iter().enumerate() {
+    println!("  {:<18}{:.2}", pill_type.name(), mean(outcomes.iter().map(|o| o.pills_eaten[i] as f64)));
+  }
+}
+
+fn main() {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+    println!("{USAGE}");
+    return;
+  }
+  let options = parse_args(&args).unwrap_or_else(|e| {
+    eprintln!("{e}\n{USAGE}");
+    exit(2);
+  });
+
+  let config = EngineConfig { tuning: options.tuning.as_ref().map(|path| tuning(path)), ..EngineConfig::default() };
+  let mut outcomes = Vec::new();
+  for seed in options.seed..options.seed + options.runs {
+    let mut player = player(&options.player, seed).unwrap_or_else(|e| {
+      eprintln!("{e}");
+      exit(2);
+    });
+    outcomes.push(simulate(seed, player.as_mut(), &config, options.max_seconds));
+  }
+  report(&options, &outcomes);
+}
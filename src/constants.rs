@@ -1,6 +1,13 @@
+// defaults for EngineConfig
+
+pub const CANVAS_ID: &str = "snek_canvas";
 pub const CANVAS_HEIGHT: u32 = 600;
 pub const CANVAS_WIDTH: u32 = 800;
+pub const BACKGROUND_COLOR: &str = "black";
 
 pub const FRAMES_PER_SEC: u32 = 30;
-pub const FRAME_LENGTH: f64 = 1.0 / (FRAMES_PER_SEC as f64);
+// most simulation steps run per rendered frame. time beyond this is dropped
+pub const MAX_STEPS_PER_FRAME: u32 = 4;
 
+// gamepad stick deflection (0.0 to 1.0) that is ignored
+pub const GAMEPAD_DEAD_ZONE: f64 = 0.25;
@@ -0,0 +1,163 @@
+use crate::engine::events::Action;
+use crate::util;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// localStorage entry the bindings are persisted under
+const STORAGE_KEY: &str = "snek.key_bindings";
+
+// maps KeyboardEvent.code values (physical key positions such as "KeyW") to actions
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct KeyBindings {
+  actions: BTreeMap<String, Action>,
+}
+
+impl Default for KeyBindings {
+  fn default() -> Self {
+    let mut bindings = Self { actions: BTreeMap::new() };
+    for (code, action) in [
+      ("ArrowUp", Action::Up), ("ArrowDown", Action::Down), ("ArrowLeft", Action::Left), ("ArrowRight", Action::Right),
+      ("KeyW", Action::Up), ("KeyS", Action::Down), ("KeyA", Action::Left), ("KeyD", Action::Right),
+      ("KeyP", Action::Pause), ("Escape", Action::Pause),
+      ("Enter", Action::Confirm), ("Space", Action::Confirm),
+      ("Backspace", Action::Back),
+      ("KeyR", Action::Restart),
+      ("ShiftLeft", Action::Boost), ("ShiftRight", Action::Boost),
+    ] {
+      bindings.bind(code, action);
+    }
+    bindings
+  }
+}
+
+impl KeyBindings {
+  // the action bound to a code, if any
+  pub fn action(&self, code: &str) -> Option<Action> {
+    self.actions.get(code).copied()
+  }
+
+  // bind a code to an action, replacing whatever it was bound to before. several codes can
+  // be bound to the same action
+  pub fn bind(&mut self, code: &str, action: Action) {
+    self.actions.insert(code.to_string(), action);
+  }
+
+  pub fn unbind(&mut self, code: &str) {
+    self.actions.remove(code);
+  }
+
+  pub fn to_json(&self) -> String {
+    serde_json::to_string(self).unwrap()
+  }
+
+  pub fn from_json(s: &str) -> Result<Self, String> {
+    serde_json::from_str(s).map_err(|e| format!("invalid key bindings: {e}"))
+  }
+
+  // the bindings saved by a previous session, or the defaults if there are none or they
+  // can't be read
+  pub fn load() -> Self {
+    util::get_item(STORAGE_KEY)
+      .and_then(|s| Self::from_json(&s).ok())
+      .map(Self::with_defaults)
+      .unwrap_or_default()
+  }
+
+  // these bindings plus the default codes of any action they leave unbound, e.g. one added
+  // since they were saved. a default code already bound to something else is left alone
+  fn with_defaults(mut self) -> Self {
+    let bound: Vec<Action> = self.actions.values().copied().collect();
+    for (code, action) in Self::default().actions {
+      if !bound.contains(&action) {
+        self.actions.entry(code).or_insert(action);
+      }
+    }
+    self
+  }
+
+  // persist the bindings for future sessions. storage may be unavailable (e.g. private
+  // browsing), in which case the bindings only last for this session
+  pub fn save(&self) {
+    util::set_item(STORAGE_KEY, &self.to_json());
+  }
+}
+
+// the codes currently held down and the actions they pressed, so an action bound to
+// several keys stays held until the last of them comes up
+#[derive(Default)]
+pub struct HeldKeys {
+  codes: BTreeMap<String, Action>,
+}
+
+impl HeldKeys {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // the action to press for code going down, if it's bound to one
+  pub fn key_down(&mut self, code: &str, bindings: &KeyBindings) -> Option<Action> {
+    let action = bindings.action(code)?;
+    self.codes.insert(code.to_string(), action);
+    Some(action)
+  }
+
+  // the action to release for code coming up, once no other held code is holding it
+  pub fn key_up(&mut self, code: &str) -> Option<Action> {
+    let action = self.codes.remove(code)?;
+    if self.codes.values().any(|held| *held == action) {
+      None
+    } else {
+      Some(action)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rebinding_a_code_replaces_its_action() {
+    let mut bindings = KeyBindings::default();
+    assert_eq!(bindings.action("KeyW"), Some(Action::Up));
+    assert_eq!(bindings.action("KeyZ"), None);
+
+    // azerty
+    bindings.bind("KeyZ", Action::Up);
+    bindings.bind("KeyQ", Action::Left);
+    bindings.unbind("KeyA");
+    bindings.bind("KeyW", Action::Down);
+    assert_eq!(bindings.action("KeyZ"), Some(Action::Up));
+    assert_eq!(bindings.action("KeyQ"), Some(Action::Left));
+    assert_eq!(bindings.action("KeyA"), None);
+    assert_eq!(bindings.action("KeyW"), Some(Action::Down));
+
+    assert_eq!(KeyBindings::from_json(&bindings.to_json()).unwrap(), bindings);
+  }
+
+  #[test]
+  fn saved_bindings_keep_the_defaults_of_actions_they_leave_out() {
+    // saved before boost existed, with KeyA taken for up
+    let saved = KeyBindings::from_json(r#"{"ArrowUp":"up","KeyA":"up","ArrowLeft":"left"}"#).unwrap();
+    let bindings = saved.with_defaults();
+    assert_eq!(bindings.action("ShiftLeft"), Some(Action::Boost));
+    assert_eq!(bindings.action("Enter"), Some(Action::Confirm));
+    assert_eq!(bindings.action("KeyA"), Some(Action::Up));
+    // up and left were saved, so their other default keys stay unbound
+    assert_eq!(bindings.action("KeyW"), None);
+    assert_eq!(bindings.action("KeyD"), Some(Action::Right));
+  }
+
+  #[test]
+  fn an_action_stays_held_until_its_last_key_is_released() {
+    let bindings = KeyBindings::default();
+    let mut held = HeldKeys::new();
+    assert_eq!(held.key_down("ArrowUp", &bindings), Some(Action::Up));
+    assert_eq!(held.key_down("KeyW", &bindings), Some(Action::Up));
+    assert_eq!(held.key_down("KeyZ", &bindings), None);
+    assert_eq!(held.key_up("ArrowUp"), None);
+    assert_eq!(held.key_up("KeyZ"), None);
+    assert_eq!(held.key_up("KeyW"), Some(Action::Up));
+  }
+}
@@ -0,0 +1,101 @@
+use crate::engine::{EngineError, Point2d, Rect, Renderer};
+use std::f64::consts::PI;
+use wasm_bindgen::prelude::*;
+
+// Renderer backed by a CanvasRenderingContext2d
+pub struct CanvasRenderer {
+  // a rect that covers the canvas, in logical coordinates
+  bounds: Rect,
+  // when the clear method is called, this color is drawn to the entire canvas
+  bg_color: String,
+  // rendering context for the canvas
+  cxt: web_sys::CanvasRenderingContext2d,
+}
+
+impl CanvasRenderer {
+  pub fn new(
+    canvas: &web_sys::HtmlCanvasElement,
+    bounds: Rect,
+    bg_color: String,
+  ) -> Result<Self, EngineError> {
+    // get the rendering context
+    let cxt = canvas
+        .get_context("2d").ok().flatten()
+        .and_then(|cxt| cxt.dyn_into::<web_sys::CanvasRenderingContext2d>().ok())
+        .ok_or(EngineError::MissingContext)?;
+    Ok(Self { bounds, bg_color, cxt })
+  }
+
+  // set the number of canvas pixels per logical unit. resizing the canvas resets its
+  // transform, so this is applied before every frame
+  pub fn set_scale(&self, scale: f64) {
+    let _ = self.cxt.set_transform(scale, 0.0, 0.0, scale, 0.0, 0.0);
+  }
+
+  // fill and/or stroke the current path. newer web-sys deprecates set_fill_style and
+  // set_stroke_style for versions taking a &str, which 0.3.64 doesn't have
+  #[allow(deprecated)]
+  fn finish(&self, fill_style: Option<&str>, stroke_style: Option<&str>, line_width: Option<f64>) {
+    if let Some(fill_style) = fill_style {
+      self.cxt.set_fill_style(&JsValue::from_str(fill_style));
+      self.cxt.fill();
+    }
+    if let Some(stroke_style) = stroke_style {
+      self.cxt.set_line_width(line_width.unwrap_or(1.0));
+      self.cxt.set_stroke_style(&JsValue::from_str(stroke_style));
+      self.cxt.stroke();
+    }
+  }
+}
+
+impl Renderer for CanvasRenderer {
+  fn clear(&self) {
+    self.rect(&self.bounds, Some(&self.bg_color), None, None);
+  }
+
+  fn rect(
+    &self,
+    rect: &Rect,
+    fill_style: Option<&str>,
+    stroke_style: Option<&str>,
+    line_width: Option<f64>
+  ) {
+    let Rect { x, y, width, height } = rect.clone();
+    self.cxt.begin_path();
+    self.cxt.rect(x, y, width, height);
+    self.finish(fill_style, stroke_style, line_width);
+  }
+
+  fn path(&self, points: &[Point2d], stroke_style: &str, line_width: Option<f64>) {
+    if points.is_empty() {
+      return;
+    }
+    self.cxt.begin_path();
+    let Point2d { x, y } = points[0];
+    self.cxt.move_to(x, y);
+    for Point2d { x, y } in &points[1..] {
+      self.cxt.line_to(*x, *y);
+    }
+    self.finish(None, Some(stroke_style), line_width);
+  }
+
+  fn circle(
+    &self,
+    position: &Point2d,
+    radius: f64,
+    fill_style: Option<&str>,
+    stroke_style: Option<&str>,
+    line_width: Option<f64>
+  ) {
+    self.cxt.begin_path();
+    let _ = self.cxt.arc(position.x, position.y, radius, 0.0, 2.0*PI);
+    self.finish(fill_style, stroke_style, line_width);
+  }
+
+  #[allow(deprecated)]
+  fn text(&self, s: &str, color: &str, size: u8, x: f64, y: f64) {
+    self.cxt.set_font(&format!("{size}pt sans-serif"));
+    self.cxt.set_fill_style(&JsValue::from_str(color));
+    let _ = self.cxt.fill_text(s, x, y);
+  }
+}
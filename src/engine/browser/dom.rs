@@ -0,0 +1,105 @@
+use crate::engine::error::EngineError;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+
+// there's no window in e.g. a web worker
+pub fn window() -> Result<web_sys::Window, EngineError> {
+  web_sys::window().ok_or(EngineError::Unsupported("window"))
+}
+
+pub fn document() -> Result<web_sys::Document, EngineError> {
+  window()?.document().ok_or(EngineError::Unsupported("document"))
+}
+
+// the canvas element with this id, if there is one
+pub fn get_canvas(id: &str) -> Option<web_sys::HtmlCanvasElement> {
+  let canvas = document().ok()?
+      .get_element_by_id(id)?
+      .dyn_into::<web_sys::HtmlCanvasElement>()
+      .ok()?;
+  let _ = canvas.focus();
+  Some(canvas)
+}
+
+pub type RafClosure = Closure<dyn FnMut(f64)>;
+
+pub fn request_animation_frame(callback: &RafClosure) -> Result<i32, EngineError> {
+  window()?
+    .request_animation_frame(callback.as_ref().unchecked_ref())
+    .map_err(|_| EngineError::Unsupported("requestAnimationFrame"))
+}
+
+pub fn cancel_animation_frame(id: i32) {
+  if let Ok(window) = window() {
+    let _ = window.cancel_animation_frame(id);
+  }
+}
+
+pub fn create_raf_closure(f: impl FnMut(f64) + 'static) -> RafClosure {
+  Closure::wrap(Box::new(f))
+}
+
+// a listener for one type of event on target. it's removed again when this is dropped, so
+// it never touches listeners added by anything else
+pub struct EventHandler {
+  target: web_sys::EventTarget,
+  event: &'static str,
+  handler: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl EventHandler {
+  pub fn new(target: &web_sys::EventTarget, event: &'static str, handler: impl FnMut(web_sys::Event) + 'static) -> Self {
+    let handler = Closure::<dyn FnMut(web_sys::Event)>::new(handler);
+    let _ = target.add_event_listener_with_callback(event, handler.as_ref().unchecked_ref());
+    Self { target: target.clone(), event, handler }
+  }
+}
+
+impl Drop for EventHandler {
+  fn drop(&mut self) {
+    let _ = self.target.remove_event_listener_with_callback(self.event, self.handler.as_ref().unchecked_ref());
+  }
+}
+
+pub fn now() -> Result<f64, EngineError> {
+  let performance = window()?.performance().ok_or(EngineError::Unsupported("performance"))?;
+  Ok(performance.now())
+}
+
+
+// value of a parameter in the page url's query string
+pub fn query_param(name: &str) -> Option<String> {
+  let search = web_sys::window()?.location().search().ok()?;
+  web_sys::UrlSearchParams::new_with_str(&search).ok()?.get(name)
+}
+
+// the page's localStorage, if the browser allows it
+pub fn local_storage() -> Option<web_sys::Storage> {
+  web_sys::window()?.local_storage().ok()?
+}
+
+pub fn get_item(key: &str) -> Option<String> {
+  local_storage()?.get_item(key).ok().flatten()
+}
+
+// storage may be unavailable (e.g. private browsing), in which case nothing is kept
+pub fn set_item(key: &str, value: &str) {
+  if let Some(storage) = local_storage() {
+    let _ = storage.set_item(key, value);
+  }
+}
+
+pub fn remove_item(key: &str) {
+  if let Some(storage) = local_storage() {
+    let _ = storage.remove_item(key);
+  }
+}
+
+pub fn log(message: &str) {
+  web_sys::console::log_1(&message.into());
+}
+
+// wall clock time in milliseconds since the unix epoch
+pub fn timestamp() -> f64 {
+  js_sys::Date::now()
+}
@@ -0,0 +1,187 @@
+use crate::engine::bindings::{HeldKeys, KeyBindings};
+use crate::engine::browser::dom::EventHandler;
+use crate::engine::error::EngineError;
+use crate::engine::events::{Action, InputSource, KeyState};
+use crate::engine::gamepad::{gamepad_actions, GamepadPoller};
+use crate::engine::geometry::Point2d;
+use crate::engine::pointer::gesture_action;
+use crate::engine::viewport::Viewport;
+use futures::channel::mpsc::{channel, Receiver, Sender};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+// left stick axes in the standard gamepad mapping
+const LEFT_STICK_X: u32 = 0;
+const LEFT_STICK_Y: u32 = 1;
+
+pub enum Event {
+  KeyDown(web_sys::KeyboardEvent),
+  KeyUp(web_sys::KeyboardEvent),
+  // a swipe or tap, which presses and releases an action within the same step
+  Gesture(Action),
+}
+
+// input events that can be waiting for the game loop to read them
+const INPUT_QUEUE_SIZE: usize = 32;
+
+// queue an input event. if the queue is full the event is dropped and counted, rather than
+// taking down the game
+fn send(sender: &mut Sender<Event>, dropped: &Cell<u32>, evt: Event) {
+  if sender.try_send(evt).is_err() {
+    dropped.set(dropped.get() + 1);
+  }
+}
+
+pub struct KeyPressProcessor {
+  receiver: Receiver<Event>,
+  // events dropped because the queue was full
+  dropped: Rc<Cell<u32>>,
+  // shared so bindings can be changed while the game is running
+  bindings: Rc<RefCell<KeyBindings>>,
+  held_keys: HeldKeys,
+  gamepads: GamepadPoller,
+  key_state: KeyState,
+  // removed from the canvas when the processor is dropped
+  _handlers: Vec<EventHandler>,
+}
+
+impl KeyPressProcessor {
+  pub fn new(
+    canvas: &web_sys::HtmlCanvasElement,
+    viewport: Rc<Cell<Viewport>>,
+    bindings: Rc<RefCell<KeyBindings>>,
+    gamepad_dead_zone: f64,
+  ) -> Self {
+    let (s, r) = channel::<Event>(INPUT_QUEUE_SIZE);
+    let dropped = Rc::new(Cell::new(0));
+
+    let mut handlers = Vec::new();
+
+    let (mut s1, d) = (s.clone(), dropped.clone());
+    handlers.push(EventHandler::new(canvas, "keydown", move |evt| {
+      let evt: web_sys::KeyboardEvent = evt.unchecked_into();
+      // auto-repeat events from holding a key down are not new presses
+      if !evt.repeat() {
+        send(&mut s1, &d, Event::KeyDown(evt));
+      }
+    }));
+
+    let (mut s2, d) = (s.clone(), dropped.clone());
+    handlers.push(EventHandler::new(canvas, "keyup", move |evt| {
+      send(&mut s2, &d, Event::KeyUp(evt.unchecked_into()));
+    }));
+
+    // touch and pen input: remember where the pointer went down and turn it into a
+    // gesture when it comes back up. mouse clicks are left alone. pointer positions are
+    // mapped into the game's logical coordinates, whatever size the canvas is shown at
+    let pointer_start: Rc<RefCell<Option<Point2d>>> = Rc::new(RefCell::new(None));
+
+    let start = pointer_start.clone();
+    let (v, c) = (viewport.clone(), canvas.clone());
+    handlers.push(EventHandler::new(canvas, "pointerdown", move |evt| {
+      let evt: web_sys::PointerEvent = evt.unchecked_into();
+      if evt.pointer_type() != "mouse" {
+        evt.prevent_default();
+        // keep getting the pointer's events if a swipe ends off the canvas
+        let _ = c.set_pointer_capture(evt.pointer_id());
+        *start.borrow_mut() = Some(v.get().to_logical(&Point2d { x: evt.offset_x() as f64, y: evt.offset_y() as f64 }));
+      }
+    }));
+
+    let (mut s3, d) = (s.clone(), dropped.clone());
+    let start = pointer_start.clone();
+    let v = viewport;
+    handlers.push(EventHandler::new(canvas, "pointerup", move |evt| {
+      let evt: web_sys::PointerEvent = evt.unchecked_into();
+      if let Some(start) = start.borrow_mut().take() {
+        let viewport = v.get();
+        let end = viewport.to_logical(&Point2d { x: evt.offset_x() as f64, y: evt.offset_y() as f64 });
+        let (width, height) = viewport.logical_size();
+        let action = gesture_action(&start, &end, width, height);
+        send(&mut s3, &d, Event::Gesture(action));
+      }
+    }));
+
+    // the browser took the pointer over, e.g. to scroll or zoom, so it's not a gesture
+    let start = pointer_start;
+    handlers.push(EventHandler::new(canvas, "pointercancel", move |_evt| {
+      *start.borrow_mut() = None;
+    }));
+
+    KeyPressProcessor {
+      receiver: r,
+      dropped,
+      bindings,
+      held_keys: HeldKeys::new(),
+      gamepads: GamepadPoller::new(gamepad_dead_zone),
+      key_state: KeyState::new(),
+      _handlers: handlers,
+    }
+  }
+
+  // report input events dropped since the last check
+  pub fn check_overflow(&self) -> Result<(), EngineError> {
+    match self.dropped.replace(0) {
+      0 => Ok(()),
+      dropped => Err(EngineError::InputOverflow(dropped)),
+    }
+  }
+
+  pub fn set_gamepad_dead_zone(&mut self, dead_zone: f64) {
+    self.gamepads.set_dead_zone(dead_zone);
+  }
+}
+
+impl InputSource for KeyPressProcessor {
+  // process all key presses received since last process and return the updated state.
+  // newer futures deprecates try_next for try_recv, which 0.3.28 doesn't have
+  #[allow(deprecated)]
+  fn process(&mut self) -> KeyState {
+    self.key_state.next_step();
+    while let Ok(Some(evt)) = self.receiver.try_next() {
+      match evt {
+        Event::KeyDown(evt) => {
+          if let Some(action) = self.held_keys.key_down(&evt.code(), &self.bindings.borrow()) {
+            self.key_state.press(action);
+          }
+        },
+        Event::KeyUp(evt) => {
+          if let Some(action) = self.held_keys.key_up(&evt.code()) {
+            self.key_state.release(action);
+          }
+        },
+        Event::Gesture(action) => {
+          self.key_state.press(action);
+          self.key_state.release(action);
+        },
+      }
+    }
+    let held = read_gamepads(self.gamepads.dead_zone());
+    self.gamepads.apply(held, &mut self.key_state);
+    self.key_state.clone()
+  }
+}
+
+// every action held on a connected gamepad
+fn read_gamepads(dead_zone: f64) -> Vec<Action> {
+  let mut held = Vec::new();
+  let Some(gamepads) = web_sys::window().and_then(|w| w.navigator().get_gamepads().ok()) else {
+    return held;
+  };
+  // disconnected slots in the list are null
+  for gamepad in gamepads.iter().filter_map(|g| g.dyn_into::<web_sys::Gamepad>().ok()) {
+    if !gamepad.connected() {
+      continue;
+    }
+    let buttons = gamepad.buttons();
+    let pressed = |i: u32| buttons.get(i).dyn_into::<web_sys::GamepadButton>()
+      .map(|b| b.pressed())
+      .unwrap_or(false);
+    let axes = gamepad.axes();
+    let x = axes.get(LEFT_STICK_X).as_f64().unwrap_or(0.0);
+    let y = axes.get(LEFT_STICK_Y).as_f64().unwrap_or(0.0);
+    held.extend(gamepad_actions(pressed, x, y, dead_zone));
+  }
+  held
+}
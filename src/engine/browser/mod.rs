@@ -0,0 +1,281 @@
+mod canvas;
+pub mod dom;
+mod input;
+
+pub use canvas::*;
+pub use input::*;
+
+use crate::engine::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+// handle to a running game loop
+pub struct Engine {
+  // every key state fed to the game so far
+  replay: Rc<RefCell<Replay>>,
+  // cleared when a saved run is continued, since replaying its inputs from the seed
+  // wouldn't lead to the same run
+  replayable: Rc<Cell<bool>>,
+  // keyboard bindings used by the running game
+  bindings: Rc<RefCell<KeyBindings>>,
+  // while paused, the game is drawn but not updated
+  paused: Rc<Cell<bool>>,
+  timestep: Rc<RefCell<Timestep>>,
+  // called with every event the game raises
+  listeners: Rc<RefCell<Vec<Listener>>>,
+  // set to the seed to restart the game with at the start of the next frame
+  restart: Rc<Cell<Option<u64>>>,
+  // cleared to stop the game loop for good
+  running: Rc<Cell<bool>>,
+  // the frame callback, which owns the game, and the id of the frame it's waiting for.
+  // dropped on stop, since the callback holds the Rc it's stored in
+  frame: Rc<RefCell<Option<dom::RafClosure>>>,
+  frame_id: Rc<Cell<i32>>,
+  // the engine's listeners on the page, removed on stop
+  handlers: RefCell<Vec<dom::EventHandler>>,
+}
+
+impl Engine {
+  // run the game. if a replay is given, it drives the game instead of the keyboard
+  pub async fn start(
+    game: impl Game + 'static,
+    config: EngineConfig,
+    playback: Option<Replay>,
+  ) -> Result<Engine, EngineError> {
+    config.validate()?;
+    // get the canvas and fit it to its container. the game always draws in the logical
+    // width x height space, whatever size the canvas is shown at
+    let canvas = dom::get_canvas(&config.canvas_id)
+        .ok_or_else(|| EngineError::MissingCanvas(config.canvas_id.clone()))?;
+    let viewport = Rc::new(Cell::new(fit_canvas(&canvas, &config)));
+    let window = dom::window()?;
+    let mut handlers = Vec::new();
+    let (v, c, cfg) = (viewport.clone(), canvas.clone(), config.clone());
+    handlers.push(dom::EventHandler::new(&window, "resize", move |_| v.set(fit_canvas(&c, &cfg))));
+
+    let renderer = CanvasRenderer::new(&canvas, config.bounds(), config.background_color.clone())?;
+    let bindings = Rc::new(RefCell::new(KeyBindings::load()));
+    // the player's input always controls the engine (e.g. pausing). during playback the game
+    // itself is driven by the replay
+    let mut controls = KeyPressProcessor::new(&canvas, viewport.clone(), bindings.clone(), config.gamepad_dead_zone);
+    let mut playback = playback.map(ReplayPlayer::new);
+    let game = game.init(&config).await?;
+    let replay = Rc::new(RefCell::new(Replay::new(game.seed())));
+    // shared with the visibility handler, which suspends the game
+    let game = Rc::new(RefCell::new(game));
+    let recording = replay.clone();
+    let replayable = Rc::new(Cell::new(true));
+    let is_replayable = replayable.clone();
+    let mut last_frame = dom::now()?;
+    let timestep = Rc::new(RefCell::new(Timestep::new(config.step_length(), config.max_steps_per_frame)));
+    let clock = timestep.clone();
+
+    // pause whenever the page is hidden or loses focus, so no time passes in the game
+    // while the player can't see it
+    let paused = Rc::new(Cell::new(false));
+    let document = dom::document()?;
+    let (p, d, suspended) = (paused.clone(), document.clone(), game.clone());
+    handlers.push(dom::EventHandler::new(&document, "visibilitychange", move |_| {
+      if d.hidden() {
+        p.set(true);
+        if let Ok(mut game) = suspended.try_borrow_mut() {
+          game.suspend();
+        }
+      }
+    }));
+    let p = paused.clone();
+    handlers.push(dom::EventHandler::new(&canvas, "blur", move |_| p.set(true)));
+    let p = paused.clone();
+    handlers.push(dom::EventHandler::new(&window, "blur", move |_| p.set(true)));
+    let is_paused = paused.clone();
+    let mut was_paused = false;
+    let listeners: Rc<RefCell<Vec<Listener>>> = Rc::new(RefCell::new(Vec::new()));
+    let notify = listeners.clone();
+    let restart: Rc<Cell<Option<u64>>> = Rc::new(Cell::new(None));
+    let pending_restart = restart.clone();
+    let running = Rc::new(Cell::new(true));
+    let is_running = running.clone();
+
+    let f: Rc<RefCell<Option<dom::RafClosure>>> = Rc::new(RefCell::new(None));
+    let g = f.clone();
+    let frame_id = Rc::new(Cell::new(0));
+    let next_frame_id = frame_id.clone();
+
+    let frame = dom::create_raf_closure(move |perf: f64| {
+      if !is_running.get() {
+        // the loop ends by not requesting another frame
+        return;
+      }
+      let mut game = game.borrow_mut();
+      let delta = (perf - last_frame) / 1000.0;  // perf is in milliseconds
+      last_frame = perf;
+      if let Some(seed) = pending_restart.take() {
+        // a restart also ends any replay being played back
+        game.restart(seed);
+        playback = None;
+        *recording.borrow_mut() = Replay::new(seed);
+        is_replayable.set(true);
+        clock.borrow_mut().reset();
+        is_paused.set(false);
+      }
+      let steps = if is_paused.get() {
+        // time spent paused never reaches the clock, so it isn't caught up on when resuming.
+        // the clock keeps the time between steps it had, so the game is still drawn where
+        // it was when it paused
+        if controls.process().presses().iter().any(|action| action.resumes()) {
+          is_paused.set(false);
+        }
+        0
+      } else {
+        clock.borrow_mut().frame(delta)
+      };
+      // reported through frame_stats as well as the console
+      if let Err(error) = controls.check_overflow() {
+        if let EngineError::InputOverflow(dropped) = error {
+          clock.borrow_mut().inputs_dropped(dropped);
+        }
+        log!("{error}");
+      }
+      for _ in 0..steps {
+        let control_state = controls.process();
+        if control_state.just_pressed(Action::Pause) {
+          is_paused.set(true);
+          break;
+        }
+        if control_state.just_pressed(Action::Restart) {
+          pending_restart.set(Some(rand::random()));
+          break;
+        }
+        let key_state = match &mut playback {
+          Some(player) => player.process(),
+          None => control_state,
+        };
+        game.update(&key_state);
+        recording.borrow_mut().record(&key_state);
+        for event in game.events() {
+          if let GameEvent::RunRestored { seed } = event {
+            // restarting with the same seed restarts the continued run
+            *recording.borrow_mut() = Replay::new(seed);
+            is_replayable.set(false);
+          }
+          for listener in notify.borrow_mut().iter_mut() {
+            listener(&event);
+          }
+        }
+      }
+      if is_paused.get() != was_paused {
+        was_paused = is_paused.get();
+        game.pause_changed(was_paused);
+      }
+      renderer.set_scale(viewport.get().scale());
+      game.draw(&renderer, clock.borrow().alpha());
+      // gone if a listener stopped the engine during this frame
+      if let Some(callback) = f.borrow().as_ref() {
+        match dom::request_animation_frame(callback) {
+          Ok(id) => next_frame_id.set(id),
+          Err(error) => { log!("{error}"); },
+        }
+      }
+    });
+
+    frame_id.set(dom::request_animation_frame(&frame)?);
+    *g.borrow_mut() = Some(frame);
+
+    let handlers = RefCell::new(handlers);
+    Ok(Engine { replay, replayable, bindings, paused, timestep, listeners, restart, running, frame: g, frame_id, handlers })
+  }
+
+  // call listener with every event the game raises from now on
+  pub fn subscribe(&self, listener: impl FnMut(&GameEvent) + 'static) {
+    self.listeners.borrow_mut().push(Box::new(listener));
+  }
+
+  // stop the game loop and remove the engine's event handlers from the page. the game
+  // can't be resumed afterwards
+  pub fn stop(&self) {
+    self.running.set(false);
+    dom::cancel_animation_frame(self.frame_id.get());
+    // dropping the frame callback drops the game and its input handlers with it
+    self.frame.borrow_mut().take();
+    self.handlers.borrow_mut().clear();
+  }
+
+  // start a new run at the next frame, with the seed of the current run or a new one
+  pub fn restart(&self, same_seed: bool) {
+    let seed = if same_seed { self.replay.borrow().seed() } else { rand::random() };
+    self.restart.set(Some(seed));
+  }
+
+  // the seed of the current run
+  pub fn seed(&self) -> u64 {
+    self.replay.borrow().seed()
+  }
+
+  // the inputs recorded since the game started. none for a continued run
+  pub fn replay(&self) -> Option<Replay> {
+    self.replayable.get().then(|| self.replay.borrow().clone())
+  }
+
+  pub fn pause(&self) {
+    self.paused.set(true);
+  }
+
+  pub fn resume(&self) {
+    self.paused.set(false);
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.paused.get()
+  }
+
+  pub fn frame_stats(&self) -> FrameStats {
+    self.timestep.borrow().stats()
+  }
+
+  pub fn set_max_steps_per_frame(&self, max_steps_per_frame: u32) {
+    self.timestep.borrow_mut().set_max_steps_per_frame(max_steps_per_frame);
+  }
+
+  pub fn key_bindings(&self) -> KeyBindings {
+    self.bindings.borrow().clone()
+  }
+
+  // replace the keyboard bindings. they take effect immediately and are saved for future
+  // sessions
+  pub fn set_key_bindings(&self, bindings: KeyBindings) {
+    bindings.save();
+    *self.bindings.borrow_mut() = bindings;
+  }
+}
+
+// size the canvas to fit its parent element, with a backing store pixel for every device
+// pixel. this clears the canvas
+fn fit_canvas(canvas: &web_sys::HtmlCanvasElement, config: &EngineConfig) -> Viewport {
+  let (container_width, container_height) = canvas
+      .parent_element()
+      .map(|parent| (parent.client_width() as f64, parent.client_height() as f64))
+      .unwrap_or((0.0, 0.0));
+  let viewport = Viewport::fit(
+    config.width as f64,
+    config.height as f64,
+    container_width,
+    container_height,
+    dom::window().map_or(1.0, |window| window.device_pixel_ratio()));
+
+  let (css_width, css_height) = viewport.css_size();
+  let style = canvas.style();
+  let _ = style.set_property("width", &format!("{css_width}px"));
+  let _ = style.set_property("height", &format!("{css_height}px"));
+  let (width, height) = viewport.backing_size();
+  canvas.set_width(width);
+  canvas.set_height(height);
+  viewport
+}
+
+// errors reach javascript as Error objects
+impl From<EngineError> for JsValue {
+  fn from(error: EngineError) -> Self {
+    js_sys::Error::new(&error.to_string()).into()
+  }
+}
@@ -0,0 +1,97 @@
+use crate::constants::*;
+use crate::engine::error::EngineError;
+use crate::engine::geometry::Rect;
+use serde::{Deserialize, Serialize};
+
+// settings for a running engine. deserialized from the object passed in from javascript,
+// where every field is optional and camelCased
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EngineConfig {
+  // id of the canvas element to draw on
+  pub canvas_id: String,
+  // size of the canvas in pixels
+  pub width: u32,
+  pub height: u32,
+  // simulation steps per second
+  pub tick_rate: u32,
+  // css color the canvas is cleared to each frame
+  pub background_color: String,
+  // most simulation steps run per rendered frame. time beyond this is dropped
+  pub max_steps_per_frame: u32,
+  // gamepad stick deflection (0.0 to 1.0) that is ignored
+  pub gamepad_dead_zone: f64,
+  // gameplay numbers for the game to read, in whatever shape it expects. snek reads a
+  // Tuning from this
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tuning: Option<serde_json::Value>,
+  // seed of the first run, e.g. for a daily challenge. a random one if not given. a
+  // replay being played back uses its own
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub seed: Option<u64>,
+}
+
+impl Default for EngineConfig {
+  fn default() -> Self {
+    Self {
+      canvas_id: CANVAS_ID.to_string(),
+      width: CANVAS_WIDTH,
+      height: CANVAS_HEIGHT,
+      tick_rate: FRAMES_PER_SEC,
+      background_color: BACKGROUND_COLOR.to_string(),
+      max_steps_per_frame: MAX_STEPS_PER_FRAME,
+      gamepad_dead_zone: GAMEPAD_DEAD_ZONE,
+      tuning: None,
+      seed: None,
+    }
+  }
+}
+
+impl EngineConfig {
+  // seconds of game time simulated by each step
+  pub fn step_length(&self) -> f64 {
+    1.0 / self.tick_rate as f64
+  }
+
+  // a rect that covers the canvas
+  pub fn bounds(&self) -> Rect {
+    Rect::new(0.0, 0.0, self.width as f64, self.height as f64)
+  }
+
+  // reject settings the engine can't run with
+  pub fn validate(&self) -> Result<(), EngineError> {
+    if self.tick_rate == 0 {
+      return Err(EngineError::InitFailed("tickRate must be at least 1".to_string()));
+    }
+    if self.width == 0 || self.height == 0 {
+      return Err(EngineError::InitFailed("width and height must be at least 1".to_string()));
+    }
+    // with no steps allowed the game would never move
+    if self.max_steps_per_frame == 0 {
+      return Err(EngineError::InitFailed("maxStepsPerFrame must be at least 1".to_string()));
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn missing_fields_use_defaults() {
+    let config: EngineConfig = serde_json::from_str(r#"{ "canvasId": "game", "tickRate": 60 }"#).unwrap();
+    assert_eq!(config.canvas_id, "game");
+    assert_eq!(config.seed, None);
+    assert_eq!(config.step_length(), 1.0 / 60.0);
+    assert_eq!(config.bounds(), Rect::new(0.0, 0.0, 800.0, 600.0));
+  }
+
+  #[test]
+  fn rejects_settings_the_engine_cannot_run_with() {
+    assert_eq!(EngineConfig::default().validate(), Ok(()));
+    assert!(EngineConfig { tick_rate: 0, ..EngineConfig::default() }.validate().is_err());
+    assert!(EngineConfig { height: 0, ..EngineConfig::default() }.validate().is_err());
+    assert!(EngineConfig { max_steps_per_frame: 0, ..EngineConfig::default() }.validate().is_err());
+  }
+}
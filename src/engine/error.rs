@@ -0,0 +1,42 @@
+use std::fmt;
+
+// everything that can go wrong running a game
+#[derive(Clone, Debug, PartialEq)]
+pub enum EngineError {
+  // there's no canvas element with this id on the page
+  MissingCanvas(String),
+  // the canvas couldn't provide a 2d rendering context
+  MissingContext,
+  // input events arrived faster than the game loop read them, and this many were dropped
+  InputOverflow(u32),
+  // the game couldn't be initialized
+  InitFailed(String),
+  // the page doesn't provide a browser api the engine needs
+  Unsupported(&'static str),
+}
+
+impl fmt::Display for EngineError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      EngineError::MissingCanvas(id) => write!(f, "no canvas element with id \"{id}\""),
+      EngineError::MissingContext => write!(f, "the canvas has no 2d rendering context"),
+      EngineError::InputOverflow(dropped) => write!(f, "dropped {dropped} input events"),
+      EngineError::InitFailed(reason) => write!(f, "the game failed to initialize: {reason}"),
+      EngineError::Unsupported(api) => write!(f, "the browser doesn't provide {api}"),
+    }
+  }
+}
+
+impl std::error::Error for EngineError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn messages_name_the_problem() {
+    assert_eq!(EngineError::MissingCanvas("game".to_string()).to_string(), "no canvas element with id \"game\"");
+    assert_eq!(EngineError::InputOverflow(3).to_string(), "dropped 3 input events");
+    assert_eq!(EngineError::Unsupported("document").to_string(), "the browser doesn't provide document");
+  }
+}
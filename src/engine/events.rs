@@ -1,102 +1,139 @@
-use futures::channel::mpsc::{channel, Receiver};
-use wasm_bindgen::prelude::*;
-use web_sys;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub enum Key {
-  Up, Down, Left, Right, Other
+// logical inputs. physical keys, gamepad buttons and gestures are all mapped onto these
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+  Up, Down, Left, Right,
+  Pause, Confirm, Back, Restart, Boost,
+  // actions with a meaning only to a particular game
+  Game(u8),
 }
 
-impl Key {
-  fn from_code(code: String) -> Self {
-    match code.as_str() {
-      "ArrowUp" | "KeyW" => Key::Up,
-      "ArrowDown" | "KeyS" => Key::Down,
-      "ArrowLeft" | "KeyA" => Key::Left,
-      "ArrowRight" | "KeyD" => Key::Right,
-      _ => Key::Other,
+impl Action {
+  // parse the name used for an action in the javascript api. game defined actions are
+  // named "game:<n>"
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "up" => Some(Action::Up),
+      "down" => Some(Action::Down),
+      "left" => Some(Action::Left),
+      "right" => Some(Action::Right),
+      "pause" => Some(Action::Pause),
+      "confirm" => Some(Action::Confirm),
+      "back" => Some(Action::Back),
+      "restart" => Some(Action::Restart),
+      "boost" => Some(Action::Boost),
+      _ => name.strip_prefix("game:").and_then(|n| n.parse().ok()).map(Action::Game),
     }
   }
+
+  // pressing this while paused resumes the game. includes the directions, since that's
+  // all a tap or swipe on a touch screen can produce
+  pub fn resumes(self) -> bool {
+    matches!(self, Action::Pause | Action::Confirm | Action::Up | Action::Down | Action::Left | Action::Right)
+  }
 }
 
-#[derive(Clone, Debug)]
+// the input for one step. empty collections are left out when serialized to keep replays small
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct KeyState {
-  up: bool,
-  down: bool,
-  left: bool,
-  right: bool,
+  // actions currently held down
+  #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+  held: BTreeSet<Action>,
+  // actions pressed since the previous step, in the order they were pressed. an action can
+  // be pressed and released between two steps, so this can hold actions that are not held
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  presses: Vec<Action>,
+  // actions released since the previous step
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  releases: Vec<Action>,
 }
 
 impl KeyState {
-  fn new() -> Self {
-    Self { up: false, down: false, left: false, right: false }
+  pub fn new() -> Self {
+    Self::default()
   }
 
-  pub fn is_pressed(&self, key: Key) -> bool {
-    match key {
-      Key::Up => self.up,
-      Key::Down => self.down,
-      Key::Left => self.left,
-      Key::Right => self.right,
-      Key::Other => false,
-    }
+  // true while the action is held down
+  pub fn is_pressed(&self, action: Action) -> bool {
+    self.held.contains(&action)
+  }
+
+  // true on the step the action was pressed
+  pub fn just_pressed(&self, action: Action) -> bool {
+    self.presses.contains(&action)
+  }
+
+  // true on the step the action was released
+  pub fn just_released(&self, action: Action) -> bool {
+    self.releases.contains(&action)
+  }
+
+  pub fn presses(&self) -> &[Action] {
+    &self.presses
+  }
+
+  pub fn press(&mut self, action: Action) {
+    self.held.insert(action);
+    self.presses.push(action);
   }
 
-  fn set_key_pressed(&mut self, key: Key, value: bool) {
-    match key {
-      Key::Up => self.up = value,
-      Key::Down => self.down = value,
-      Key::Left => self.left = value,
-      Key::Right => self.right = value,
-      Key::Other => {},
+  pub fn release(&mut self, action: Action) {
+    if self.held.remove(&action) {
+      self.releases.push(action);
     }
   }
-}
 
-pub enum Event {
-  KeyDown(web_sys::KeyboardEvent),
-  KeyUp(web_sys::KeyboardEvent),
+  // forget the presses and releases of the previous step
+  pub fn next_step(&mut self) {
+    self.presses.clear();
+    self.releases.clear();
+  }
 }
 
-pub struct KeyPressProcessor {
-  receiver: Receiver<Event>,
-  key_state: KeyState,
+// something that produces the key state for each simulation step
+pub trait InputSource {
+  fn process(&mut self) -> KeyState;
 }
 
-impl KeyPressProcessor {
-  pub fn new(canvas: &web_sys::HtmlCanvasElement) -> Self {
-    let (s, r) = channel::<Event>(10);
-
-    let mut s1 = s.clone();
-    let keydown_handler = Closure::new(move |evt: web_sys::KeyboardEvent| {
-      s1.try_send(Event::KeyDown(evt)).unwrap();
-    });
-    canvas.set_onkeydown(Some(keydown_handler.as_ref().unchecked_ref()));
-    keydown_handler.forget();
-
-    let mut s2 = s.clone();
-    let keyup_handler = Closure::new(move |evt: web_sys::KeyboardEvent| {
-      s2.try_send(Event::KeyUp(evt)).unwrap();
-    });
-    canvas.set_onkeyup(Some(keyup_handler.as_ref().unchecked_ref()));
-    keyup_handler.forget();
-
-    KeyPressProcessor { receiver: r, key_state: KeyState::new() }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn presses_and_releases_last_one_step() {
+    let mut key_state = KeyState::new();
+    key_state.press(Action::Pause);
+    key_state.press(Action::Boost);
+    key_state.release(Action::Pause);
+    assert!(key_state.just_pressed(Action::Pause));
+    assert!(key_state.just_released(Action::Pause));
+    assert!(!key_state.is_pressed(Action::Pause));
+    assert!(key_state.is_pressed(Action::Boost));
+
+    key_state.next_step();
+    assert!(!key_state.just_pressed(Action::Boost));
+    assert!(key_state.is_pressed(Action::Boost));
+    assert!(key_state.presses().is_empty());
   }
 
-  // process all key presses received since last process and return the updated state
-  pub fn process(&mut self) -> KeyState {
-    while let Ok(Some(evt)) = self.receiver.try_next() {
-      match evt {
-        Event::KeyDown(evt) => {
-          self.key_state.set_key_pressed(Key::from_code(evt.code()), true);
-        },
-        Event::KeyUp(evt) => {
-          self.key_state.set_key_pressed(Key::from_code(evt.code()), false);
-        }
-      }
-    }
-    self.key_state.clone()
+  #[test]
+  fn parses_action_names() {
+    assert_eq!(Action::from_name("confirm"), Some(Action::Confirm));
+    assert_eq!(Action::from_name("game:3"), Some(Action::Game(3)));
+    assert_eq!(Action::from_name("game:x"), None);
+    assert_eq!(Action::from_name("jump"), None);
   }
-}
 
+  #[test]
+  fn touch_input_can_resume() {
+    assert!(Action::Pause.resumes());
+    assert!(Action::Confirm.resumes());
+    assert!(Action::Left.resumes());
+    assert!(!Action::Boost.resumes());
+    assert!(!Action::Restart.resumes());
+  }
+}
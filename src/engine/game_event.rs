@@ -0,0 +1,37 @@
+use serde::Serialize;
+
+// something that happened in a game that the host page may want to react to. passed to
+// javascript callbacks as an object with a `type` field, e.g. `{ type: "gameOver", won: true }`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GameEvent {
+  ScoreChanged { score: u64 },
+  GameOver { won: bool },
+  // a run saved in an earlier session was continued. it started with seed, but partway
+  // through, so the engine stops it being replayed
+  RunRestored { seed: u64 },
+}
+
+impl GameEvent {
+  // the name javascript subscribes to this event with
+  pub fn name(&self) -> &'static str {
+    match self {
+      GameEvent::ScoreChanged { .. } => "scoreChanged",
+      GameEvent::GameOver { .. } => "gameOver",
+      GameEvent::RunRestored { .. } => "runRestored",
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn serializes_with_its_name_as_the_type() {
+    let event = GameEvent::GameOver { won: true };
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["type"], event.name());
+    assert_eq!(json["won"], true);
+  }
+}
@@ -0,0 +1,103 @@
+use crate::engine::events::{Action, KeyState};
+
+// standard gamepad mapping: face buttons, start and d-pad
+const BUTTONS: [(u32, Action); 7] = [
+  (0, Action::Confirm), (1, Action::Back), (9, Action::Pause),
+  (12, Action::Up), (13, Action::Down), (14, Action::Left), (15, Action::Right),
+];
+
+// the actions held on one gamepad, given whether each button index is pressed and where
+// the left stick is
+pub fn gamepad_actions(pressed: impl Fn(u32) -> bool, x: f64, y: f64, dead_zone: f64) -> Vec<Action> {
+  let mut held: Vec<Action> = BUTTONS.iter()
+    .filter(|(i, _)| pressed(*i))
+    .map(|(_, action)| *action)
+    .collect();
+  if let Some(action) = stick_action(x, y, dead_zone) {
+    held.push(action);
+  }
+  held
+}
+
+// direction the left stick is pushed in, if it is outside the dead zone. diagonals resolve
+// to whichever axis is pushed further
+pub fn stick_action(x: f64, y: f64, dead_zone: f64) -> Option<Action> {
+  if x.abs().max(y.abs()) <= dead_zone {
+    return None;
+  }
+  if x.abs() > y.abs() {
+    Some(if x < 0.0 { Action::Left } else { Action::Right })
+  } else {
+    Some(if y < 0.0 { Action::Up } else { Action::Down })
+  }
+}
+
+// turns the buttons and left stick of every connected gamepad, polled once per step, into
+// action presses and releases
+pub struct GamepadPoller {
+  // stick deflection (0.0 to 1.0) that is ignored
+  dead_zone: f64,
+  // actions held on the previous poll
+  held: Vec<Action>,
+}
+
+impl GamepadPoller {
+  pub fn new(dead_zone: f64) -> Self {
+    Self { dead_zone, held: Vec::new() }
+  }
+
+  pub fn set_dead_zone(&mut self, dead_zone: f64) {
+    self.dead_zone = dead_zone;
+  }
+
+  pub fn dead_zone(&self) -> f64 {
+    self.dead_zone
+  }
+
+  // update key_state with the actions pressed or released since the previous poll, given
+  // every action held on a gamepad now
+  pub fn apply(&mut self, held: Vec<Action>, key_state: &mut KeyState) {
+    for action in &held {
+      if !self.held.contains(action) {
+        key_state.press(*action);
+      }
+    }
+    for action in &self.held {
+      if !held.contains(action) {
+        key_state.release(*action);
+      }
+    }
+    self.held = held;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn stick_inside_dead_zone_is_ignored() {
+    assert_eq!(stick_action(0.1, -0.2, 0.25), None);
+    assert_eq!(stick_action(0.1, -0.6, 0.25), Some(Action::Up));
+    assert_eq!(stick_action(0.7, 0.5, 0.25), Some(Action::Right));
+  }
+
+  #[test]
+  fn reads_buttons_and_stick() {
+    let held = gamepad_actions(|i| i == 0 || i == 9, -0.9, 0.1, 0.25);
+    assert_eq!(held, vec![Action::Confirm, Action::Pause, Action::Left]);
+  }
+
+  #[test]
+  fn holding_a_direction_presses_it_once() {
+    let mut poller = GamepadPoller::new(0.25);
+    let mut key_state = KeyState::new();
+    poller.apply(vec![Action::Left], &mut key_state);
+    poller.apply(vec![Action::Left], &mut key_state);
+    assert_eq!(key_state.presses(), &[Action::Left]);
+    assert!(key_state.is_pressed(Action::Left));
+
+    poller.apply(vec![], &mut key_state);
+    assert!(!key_state.is_pressed(Action::Left));
+  }
+}
@@ -1,10 +1,19 @@
-#[derive(Clone, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Point2d {
   pub x: f64,
   pub y: f64,
 }
 
-#[derive(Clone, Debug)]
+impl Point2d {
+  // the point t of the way (0.0 to 1.0) from self to other
+  pub fn lerp(&self, other: &Point2d, t: f64) -> Point2d {
+    Point2d { x: self.x + (other.x - self.x) * t, y: self.y + (other.y - self.y) * t }
+  }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Rect {
   pub x: f64,
   pub y: f64,
@@ -1,61 +1,62 @@
+mod bindings;
+mod config;
+mod error;
 mod events;
+mod game_event;
+mod gamepad;
 mod geometry;
+mod pointer;
 mod render;
-
+mod replay;
+mod scene;
+mod timestep;
+mod viewport;
+
+pub use bindings::*;
+pub use config::*;
+pub use error::*;
 pub use events::*;
+pub use game_event::*;
+pub use gamepad::*;
 pub use geometry::*;
+pub use pointer::*;
 pub use render::*;
+pub use replay::*;
+pub use scene::*;
+pub use timestep::*;
+pub use viewport::*;
+
+// runs games in a web page. everything that touches web-sys lives here
+#[cfg(feature = "browser")]
+pub mod browser;
+#[cfg(feature = "browser")]
+pub use browser::{CanvasRenderer, Engine, KeyPressProcessor};
 
 use async_trait::async_trait;
-use crate::constants::*;
-use crate::util;
-use std::cell::RefCell;
-use std::rc::Rc;
-use wasm_bindgen::prelude::*;
 
 #[async_trait(?Send)]
 pub trait Game {
-  async fn init(&self) -> Result<Box<dyn Game>, ()>;
+  async fn init(&self, config: &EngineConfig) -> Result<Box<dyn Game>, EngineError>;
+  // the seed the game's random number generator was created from
+  fn seed(&self) -> u64;
   fn update(&mut self, key_state: &KeyState);
-  fn draw(&self, renderer: &Renderer);
-}
-
-pub struct Engine {}
-
-impl Engine {
-  pub async fn start(game: impl Game + 'static) -> Result<(), String> {
-    // get the canvas and ensure it's the correct size
-    let canvas = util::get_canvas();
-    canvas.set_height(CANVAS_HEIGHT);
-    canvas.set_width(CANVAS_WIDTH);
-
-    let renderer = Renderer::new(&canvas, JsValue::from("black"));
-    let mut key_press_processor = KeyPressProcessor::new(&canvas);
-    let mut game = game.init().await.unwrap();
-    let mut last_frame = util::now();
-    let mut accumulated_time = 0.0;
-
-    let f: Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>> = Rc::new(RefCell::new(None));
-    let g = f.clone();
-
-    *g.borrow_mut() = Some(
-      util::create_raf_closure(move |perf: f64| {
-        let delta = (perf - last_frame) / 1000.0;  // perf is in milliseconds
-        accumulated_time += delta;
-        while accumulated_time > FRAME_LENGTH {
-          let key_state = key_press_processor.process();          
-          game.update(&key_state);
-          accumulated_time -= FRAME_LENGTH;
-        }
-        last_frame = perf;
-        game.draw(&renderer);
-        util::request_animation_frame(f.borrow().as_ref().unwrap()).unwrap();
-      })
-    );
-
-    util::request_animation_frame(g.borrow().as_ref().unwrap()).unwrap();
-
-    Ok(())
+  // draw the game alpha of the way (0.0 to 1.0) from its state before the last update to
+  // its current state. the simulation runs at a fixed rate, so blending between the last
+  // two steps keeps motion smooth at any display refresh rate
+  fn draw(&self, renderer: &dyn Renderer, alpha: f64);
+  // called when the engine is paused or resumed. no updates happen while paused, but the
+  // game is still drawn every frame
+  fn pause_changed(&mut self, _paused: bool) {}
+  // called when the page is hidden. the browser may discard a hidden page without further
+  // warning, so anything that should survive has to be saved now
+  fn suspend(&mut self) {}
+  // throw away the current run and go back to the state the game started in, with a
+  // random number generator created from seed
+  fn restart(&mut self, seed: u64);
+  // events raised since the last call, which the engine passes on to its listeners
+  fn events(&mut self) -> Vec<GameEvent> {
+    Vec::new()
   }
 }
 
+pub type Listener = Box<dyn FnMut(&GameEvent)>;
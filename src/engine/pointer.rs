@@ -0,0 +1,41 @@
+use crate::engine::events::Action;
+use crate::engine::geometry::Point2d;
+
+// pointer movement shorter than this (in pixels) is a tap rather than a swipe
+const SWIPE_DISTANCE: f64 = 30.0;
+
+// the direction action for a pointer that went down at start and up at end on a surface of the
+// given size. a swipe is read along its longer axis. a tap picks the side of the surface it
+// landed on, with the sides split along the diagonals
+pub fn gesture_action(start: &Point2d, end: &Point2d, width: f64, height: f64) -> Action {
+  let (dx, dy) = (end.x - start.x, end.y - start.y);
+  let (dx, dy) = if dx.abs().max(dy.abs()) >= SWIPE_DISTANCE {
+    (dx, dy)
+  } else {
+    // normalize so the diagonals of a non-square surface split it evenly
+    ((end.x - width / 2.0) / width, (end.y - height / 2.0) / height)
+  };
+  if dx.abs() > dy.abs() {
+    if dx < 0.0 { Action::Left } else { Action::Right }
+  } else if dy < 0.0 {
+    Action::Up
+  } else {
+    Action::Down
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn swipes_and_taps_map_to_directions() {
+    let p = |x, y| Point2d { x, y };
+    // swipes
+    assert_eq!(gesture_action(&p(400.0, 300.0), &p(300.0, 290.0), 800.0, 600.0), Action::Left);
+    assert_eq!(gesture_action(&p(400.0, 300.0), &p(410.0, 200.0), 800.0, 600.0), Action::Up);
+    // taps
+    assert_eq!(gesture_action(&p(700.0, 310.0), &p(700.0, 310.0), 800.0, 600.0), Action::Right);
+    assert_eq!(gesture_action(&p(420.0, 580.0), &p(420.0, 580.0), 800.0, 600.0), Action::Down);
+  }
+}
@@ -1,115 +1,172 @@
 use crate::engine::geometry::*;
-use wasm_bindgen::prelude::*;
-use web_sys;
-
-// wrapper around CanvasRenderingContext2d to simplify drawing code
-pub struct Renderer {
-  // a rect that covers the canvas
-  bounds: Rect,
-  // when the clear method is called, this color is drawn to the entire canvas
-  bg_color: JsValue,
-  // rendering context for the canvas
-  cxt: web_sys::CanvasRenderingContext2d,
+use std::cell::RefCell;
+
+// drawing operations a game needs. colors are css color strings
+pub trait Renderer {
+  // fill the entire drawing surface with the background color
+  fn clear(&self);
+
+  // draw a rectangle
+  fn rect(
+    &self,
+    rect: &Rect,
+    fill_style: Option<&str>,   // only fills if fill_style is provided
+    stroke_style: Option<&str>, // only strokes if stroke_style is provided
+    line_width: Option<f64>     // optional. defaults to 1.0 line width if not provided
+  );
+
+  // draw a path
+  fn path(
+    &self,
+    points: &[Point2d],
+    stroke_style: &str,
+    line_width: Option<f64>   // optional. defaults to 1.0 if not provided
+  );
+
+  // draw a circle
+  fn circle(
+    &self,
+    position: &Point2d,
+    radius: f64,
+    fill_style: Option<&str>,   // only fills if fill_style is provided
+    stroke_style: Option<&str>, // only strokes if stroke_style is provided
+    line_width: Option<f64>     // optional. defaults to 1.0 line width if not provided
+  );
+
+  // draw text with its baseline starting at (x, y)
+  fn text(&self, s: &str, color: &str, size: u8, x: f64, y: f64);
 }
 
-impl Renderer {
-  pub fn new(
-    canvas: &web_sys::HtmlCanvasElement,
-    bg_color: JsValue,
-  ) -> Self {
-    let bounds = Rect { x: 0.0, y: 0.0, width: canvas.width() as f64, height: canvas.height() as f64 };
-    // get the rendering context
-    let cxt = canvas
-        .get_context("2d").unwrap().unwrap()
-        .dyn_into::<web_sys::CanvasRenderingContext2d>().unwrap();
-    Self { bounds, bg_color, cxt }
+// a single call made on a Renderer
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+  Clear,
+  Rect {
+    rect: Rect,
+    fill_style: Option<String>,
+    stroke_style: Option<String>,
+    line_width: Option<f64>,
+  },
+  Path {
+    points: Vec<Point2d>,
+    stroke_style: String,
+    line_width: Option<f64>,
+  },
+  Circle {
+    position: Point2d,
+    radius: f64,
+    fill_style: Option<String>,
+    stroke_style: Option<String>,
+    line_width: Option<f64>,
+  },
+  Text {
+    text: String,
+    color: String,
+    size: u8,
+    x: f64,
+    y: f64,
+  },
+}
+
+// renderer that draws nothing and instead keeps a list of every command it receives. useful
+// for checking what a frame draws without a browser
+#[derive(Default)]
+pub struct RecordingRenderer {
+  commands: RefCell<Vec<DrawCommand>>,
+}
+
+impl RecordingRenderer {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // the commands recorded so far
+  pub fn commands(&self) -> Vec<DrawCommand> {
+    self.commands.borrow().clone()
+  }
+
+  // remove and return the commands recorded so far
+  pub fn take(&self) -> Vec<DrawCommand> {
+    self.commands.take()
+  }
+
+  fn push(&self, command: DrawCommand) {
+    self.commands.borrow_mut().push(command);
   }
+}
 
-  // fill the entire canvas with the bg_color value
-  pub fn clear(&self) {
-    self.rect(&self.bounds, Some(&self.bg_color), None, None);
+impl Renderer for RecordingRenderer {
+  fn clear(&self) {
+    self.push(DrawCommand::Clear);
   }
 
-  // draw a rectangle onto the canvas
-  pub fn rect(
+  fn rect(
     &self,
     rect: &Rect,
-    fill_style: Option<&JsValue>,   // only fills if fill_style is provided
-    stroke_style: Option<&JsValue>, // only strokes if stroke_style is provided
-    line_width: Option<f64>         // optional. defaults to 1.0 line width if not provided
+    fill_style: Option<&str>,
+    stroke_style: Option<&str>,
+    line_width: Option<f64>
   ) {
-    let Rect { x, y, width, height } = rect.clone();
-    self.cxt.begin_path();
-    self.cxt.rect(x, y, width, height);
-    if let Some(fill_style) = fill_style {
-      self.cxt.set_fill_style(fill_style);
-      self.cxt.fill();
-    }
-    if let Some(stroke_style) = stroke_style {
-      if let Some(line_width) = line_width {
-        self.cxt.set_line_width(line_width);
-      } else {
-        self.cxt.set_line_width(1.0);
-      }
-      self.cxt.set_stroke_style(stroke_style);
-      self.cxt.stroke();
-    }
+    self.push(DrawCommand::Rect {
+      rect: rect.clone(),
+      fill_style: fill_style.map(String::from),
+      stroke_style: stroke_style.map(String::from),
+      line_width,
+    });
   }
 
-  // draw a path onto the canvas
-  pub fn path(
-    &self,
-    points: &Vec<Point2d>,
-    stroke_style: &JsValue,
-    line_width: Option<f64>   // optional. defaults to 1.0 if not provided
-  ) {
-    if points.is_empty() {
-      return;
-    }
-    self.cxt.begin_path();
-    let Point2d { x, y } = points[0];
-    self.cxt.move_to(x, y);
-    for Point2d { x, y } in &points[1..] {
-      self.cxt.line_to(*x, *y);
-    }
-    if let Some(line_width) = line_width {
-      self.cxt.set_line_width(line_width);
-    } else {
-      self.cxt.set_line_width(1.0);
-    }
-    self.cxt.set_stroke_style(stroke_style);
-    self.cxt.stroke();
+  fn path(&self, points: &[Point2d], stroke_style: &str, line_width: Option<f64>) {
+    self.push(DrawCommand::Path {
+      points: points.to_vec(),
+      stroke_style: stroke_style.to_string(),
+      line_width,
+    });
   }
 
-  pub fn circle(
+  fn circle(
     &self,
     position: &Point2d,
     radius: f64,
-    fill_style: Option<&JsValue>,   // only fills if fill_style is provided
-    stroke_style: Option<&JsValue>, // only strokes if stroke_style is provided
-    line_width: Option<f64>         // optional. defaults to 1.0 line width if not provided
+    fill_style: Option<&str>,
+    stroke_style: Option<&str>,
+    line_width: Option<f64>
   ) {
-    self.cxt.begin_path(); 
-    let _ = self.cxt.arc(position.x, position.y, radius, 0.0, 2.0*3.141592);
-    if let Some(fill_style) = fill_style {
-      self.cxt.set_fill_style(fill_style);
-      self.cxt.fill();
-    }
-    if let Some(stroke_style) = stroke_style {
-      if let Some(line_width) = line_width {
-        self.cxt.set_line_width(line_width);
-      } else {
-        self.cxt.set_line_width(1.0);
-      }
-      self.cxt.set_stroke_style(stroke_style);
-      self.cxt.stroke();
-    }
+    self.push(DrawCommand::Circle {
+      position: position.clone(),
+      radius,
+      fill_style: fill_style.map(String::from),
+      stroke_style: stroke_style.map(String::from),
+      line_width,
+    });
   }
 
-  pub fn text(&self, s: &str, color: &str, size: u8, x: f64, y: f64) {
-    self.cxt.set_font(&format!("{size}pt sans-serif"));
-    self.cxt.set_fill_style(&JsValue::from_str(color));
-    self.cxt.fill_text(s, x, y).unwrap();
+  fn text(&self, s: &str, color: &str, size: u8, x: f64, y: f64) {
+    self.push(DrawCommand::Text { text: s.to_string(), color: color.to_string(), size, x, y });
   }
 }
 
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn records_commands_in_order() {
+    let renderer = RecordingRenderer::new();
+    renderer.clear();
+    renderer.circle(&Point2d { x: 1.0, y: 2.0 }, 5.0, Some("red"), None, None);
+    renderer.text("hi", "white", 12, 3.0, 4.0);
+
+    assert_eq!(renderer.take(), vec![
+      DrawCommand::Clear,
+      DrawCommand::Circle {
+        position: Point2d { x: 1.0, y: 2.0 },
+        radius: 5.0,
+        fill_style: Some("red".to_string()),
+        stroke_style: None,
+        line_width: None,
+      },
+      DrawCommand::Text { text: "hi".to_string(), color: "white".to_string(), size: 12, x: 3.0, y: 4.0 },
+    ]);
+    assert!(renderer.commands().is_empty());
+  }
+}
@@ -0,0 +1,98 @@
+use crate::engine::events::{InputSource, KeyState};
+use serde::{Deserialize, Serialize};
+
+// everything needed to reproduce a run: the seed the game was created with and the key
+// state fed to each update. the key state rarely changes between steps, so it is stored
+// as a list of (number of steps, key state) runs
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Replay {
+  seed: u64,
+  steps: Vec<(u32, KeyState)>,
+}
+
+impl Replay {
+  pub fn new(seed: u64) -> Self {
+    Self { seed, steps: Vec::new() }
+  }
+
+  pub fn seed(&self) -> u64 { self.seed }
+
+  // total number of recorded steps
+  pub fn step_count(&self) -> u64 {
+    self.steps.iter().map(|(n, _)| *n as u64).sum()
+  }
+
+  // append the key state for one step
+  pub fn record(&mut self, key_state: &KeyState) {
+    match self.steps.last_mut() {
+      Some((n, last)) if last == key_state => { *n += 1; },
+      _ => { self.steps.push((1, key_state.clone())); },
+    }
+  }
+
+  pub fn to_json(&self) -> String {
+    serde_json::to_string(self).unwrap()
+  }
+
+  pub fn from_json(s: &str) -> Result<Self, String> {
+    serde_json::from_str(s).map_err(|e| format!("invalid replay: {e}"))
+  }
+}
+
+// input source that feeds a recorded replay back one step at a time. once the replay runs
+// out, no keys are pressed
+pub struct ReplayPlayer {
+  replay: Replay,
+  // index into replay.steps and the number of steps already played from it
+  run: usize,
+  played: u32,
+}
+
+impl ReplayPlayer {
+  pub fn new(replay: Replay) -> Self {
+    Self { replay, run: 0, played: 0 }
+  }
+}
+
+impl InputSource for ReplayPlayer {
+  fn process(&mut self) -> KeyState {
+    while let Some((n, key_state)) = self.replay.steps.get(self.run) {
+      if self.played < *n {
+        self.played += 1;
+        return key_state.clone();
+      }
+      self.run += 1;
+      self.played = 0;
+    }
+    KeyState::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::Action;
+
+  #[test]
+  fn playback_reproduces_recorded_steps() {
+    let idle = KeyState::new();
+    let mut up = KeyState::new();
+    up.press(Action::Up);
+    let steps = vec![idle.clone(), idle.clone(), up.clone(), up.clone(), up, idle];
+
+    let mut replay = Replay::new(42);
+    for key_state in &steps {
+      replay.record(key_state);
+    }
+    assert_eq!(replay.steps.len(), 3);
+    assert_eq!(replay.step_count(), 6);
+
+    let replay = Replay::from_json(&replay.to_json()).unwrap();
+    assert_eq!(replay.seed(), 42);
+    let mut player = ReplayPlayer::new(replay);
+    for key_state in &steps {
+      assert_eq!(&player.process(), key_state);
+    }
+    assert_eq!(player.process(), KeyState::new());
+  }
+}
@@ -0,0 +1,140 @@
+use crate::engine::events::KeyState;
+use crate::engine::render::Renderer;
+
+// one screen of a game, e.g. a title menu, gameplay or a pause overlay. every scene in a
+// stack works on the same context, which holds whatever state the scenes share
+pub trait Scene<C> {
+  // handle one simulation step of input and say how the stack should change
+  fn update(&mut self, context: &mut C, key_state: &KeyState) -> SceneChange<C>;
+  fn draw(&self, context: &C, renderer: &dyn Renderer, alpha: f64);
+  // an overlay is drawn on top of the scene below it instead of replacing it
+  fn is_overlay(&self) -> bool {
+    false
+  }
+}
+
+// what a scene's update asks of the stack
+pub enum SceneChange<C> {
+  None,
+  // put a new scene on top. it's updated from the next step on
+  Push(Box<dyn Scene<C>>),
+  // remove this scene, going back to the one below
+  Pop,
+  // swap this scene for another
+  Replace(Box<dyn Scene<C>>),
+}
+
+// scenes on top of each other. only the top scene is updated. scenes are drawn from the
+// bottom up, starting at the highest one that isn't an overlay
+pub struct SceneStack<C> {
+  scenes: Vec<Box<dyn Scene<C>>>,
+}
+
+impl<C> SceneStack<C> {
+  pub fn new(root: Box<dyn Scene<C>>) -> Self {
+    Self { scenes: vec![root] }
+  }
+
+  // number of scenes on the stack
+  pub fn depth(&self) -> usize {
+    self.scenes.len()
+  }
+
+  pub fn push(&mut self, scene: Box<dyn Scene<C>>) {
+    self.scenes.push(scene);
+  }
+
+  // remove the top scene. the bottom scene is never removed, so there is always something
+  // to update and draw
+  pub fn pop(&mut self) -> Option<Box<dyn Scene<C>>> {
+    if self.scenes.len() > 1 { self.scenes.pop() } else { None }
+  }
+
+  pub fn replace(&mut self, scene: Box<dyn Scene<C>>) {
+    self.scenes.pop();
+    self.scenes.push(scene);
+  }
+
+  pub fn apply(&mut self, change: SceneChange<C>) {
+    match change {
+      SceneChange::None => {},
+      SceneChange::Push(scene) => self.push(scene),
+      SceneChange::Pop => { self.pop(); },
+      SceneChange::Replace(scene) => self.replace(scene),
+    }
+  }
+
+  pub fn update(&mut self, context: &mut C, key_state: &KeyState) {
+    if let Some(top) = self.scenes.last_mut() {
+      let change = top.update(context, key_state);
+      self.apply(change);
+    }
+  }
+
+  pub fn draw(&self, context: &C, renderer: &dyn Renderer, alpha: f64) {
+    let bottom = self.scenes.iter().rposition(|scene| !scene.is_overlay()).unwrap_or(0);
+    for scene in &self.scenes[bottom..] {
+      scene.draw(context, renderer, alpha);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::events::Action;
+  use crate::engine::render::{DrawCommand, RecordingRenderer};
+
+  // draws its name. confirm pushes an overlay, back pops
+  struct Named(&'static str, bool);
+
+  impl Scene<Vec<&'static str>> for Named {
+    fn update(&mut self, updated: &mut Vec<&'static str>, key_state: &KeyState) -> SceneChange<Vec<&'static str>> {
+      updated.push(self.0);
+      if key_state.just_pressed(Action::Confirm) {
+        SceneChange::Push(Box::new(Named("overlay", true)))
+      } else if key_state.just_pressed(Action::Back) {
+        SceneChange::Pop
+      } else {
+        SceneChange::None
+      }
+    }
+
+    fn draw(&self, _context: &Vec<&'static str>, renderer: &dyn Renderer, _alpha: f64) {
+      renderer.text(self.0, "white", 10, 0.0, 0.0);
+    }
+
+    fn is_overlay(&self) -> bool {
+      self.1
+    }
+  }
+
+  fn drawn(stack: &SceneStack<Vec<&'static str>>) -> Vec<String> {
+    let renderer = RecordingRenderer::new();
+    stack.draw(&Vec::new(), &renderer, 1.0);
+    renderer.take().into_iter().filter_map(|command| match command {
+      DrawCommand::Text { text, .. } => Some(text),
+      _ => None,
+    }).collect()
+  }
+
+  #[test]
+  fn updates_the_top_scene_and_draws_through_overlays() {
+    let mut stack = SceneStack::new(Box::new(Named("title", false)));
+    stack.replace(Box::new(Named("play", false)));
+    let mut updated = Vec::new();
+    let mut confirm = KeyState::new();
+    confirm.press(Action::Confirm);
+    stack.update(&mut updated, &confirm);
+    stack.update(&mut updated, &KeyState::new());
+    assert_eq!(updated, vec!["play", "overlay"]);
+    assert_eq!(drawn(&stack), vec!["play", "overlay"]);
+
+    let mut back = KeyState::new();
+    back.press(Action::Back);
+    stack.update(&mut updated, &back);
+    stack.update(&mut updated, &back);
+    assert_eq!(stack.depth(), 1);
+    assert_eq!(drawn(&stack), vec!["play"]);
+  }
+}
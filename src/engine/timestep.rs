@@ -0,0 +1,105 @@
+use serde::Serialize;
+
+// how the fixed timestep loop has kept up with real time
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct FrameStats {
+  // frames rendered while not paused
+  pub frames: u64,
+  // simulation steps run
+  pub steps: u64,
+  // frames that needed more steps than allowed
+  pub capped_frames: u64,
+  // seconds of game time skipped because frames were capped
+  pub dropped_time: f64,
+  // input events lost because they arrived faster than the loop read them
+  pub dropped_inputs: u64,
+}
+
+// turns the real time between rendered frames into a number of fixed length simulation steps
+pub struct Timestep {
+  step_length: f64,
+  // a slow frame can need more steps than a frame can afford to run, which makes the next
+  // frame slower still. any time beyond this many steps is dropped instead
+  max_steps_per_frame: u32,
+  // real time not yet simulated
+  accumulated_time: f64,
+  stats: FrameStats,
+}
+
+impl Timestep {
+  pub fn new(step_length: f64, max_steps_per_frame: u32) -> Self {
+    Self { step_length, max_steps_per_frame, accumulated_time: 0.0, stats: FrameStats::default() }
+  }
+
+  pub fn set_max_steps_per_frame(&mut self, max_steps_per_frame: u32) {
+    self.max_steps_per_frame = max_steps_per_frame;
+  }
+
+  pub fn stats(&self) -> FrameStats {
+    self.stats.clone()
+  }
+
+  // add the seconds since the previous frame and return how many steps to run this frame
+  pub fn frame(&mut self, delta: f64) -> u32 {
+    self.stats.frames += 1;
+    self.accumulated_time += delta;
+    let mut steps = 0;
+    while self.accumulated_time > self.step_length {
+      if steps == self.max_steps_per_frame {
+        let dropped = self.accumulated_time - self.accumulated_time % self.step_length;
+        self.accumulated_time -= dropped;
+        self.stats.dropped_time += dropped;
+        self.stats.capped_frames += 1;
+        break;
+      }
+      self.accumulated_time -= self.step_length;
+      steps += 1;
+    }
+    self.stats.steps += steps as u64;
+    steps
+  }
+
+  // how far (0.0 to 1.0) real time is between the last step run and the next one
+  pub fn alpha(&self) -> f64 {
+    (self.accumulated_time / self.step_length).min(1.0)
+  }
+
+  // count input events the loop had to drop
+  pub fn inputs_dropped(&mut self, dropped: u32) {
+    self.stats.dropped_inputs += dropped as u64;
+  }
+
+  // forget any time not yet simulated, e.g. while paused
+  pub fn reset(&mut self) {
+    self.accumulated_time = 0.0;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn slow_frames_are_capped() {
+    let mut timestep = Timestep::new(0.1, 3);
+    assert_eq!(timestep.frame(0.25), 2);
+    assert_eq!(timestep.frame(0.01), 0);
+    // 0.06 left over plus a 1 second frame: 10 steps are due but only 3 run
+    assert_eq!(timestep.frame(1.0), 3);
+    assert_eq!(timestep.frame(0.0), 0);
+
+    let stats = timestep.stats();
+    assert_eq!(stats.frames, 4);
+    assert_eq!(stats.steps, 5);
+    assert_eq!(stats.capped_frames, 1);
+    assert!((stats.dropped_time - 0.7).abs() < 1e-9);
+  }
+
+  #[test]
+  fn counts_dropped_inputs() {
+    let mut timestep = Timestep::new(0.1, 3);
+    timestep.inputs_dropped(2);
+    timestep.inputs_dropped(5);
+    assert_eq!(timestep.stats().dropped_inputs, 7);
+  }
+}
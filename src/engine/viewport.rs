@@ -0,0 +1,74 @@
+use crate::engine::geometry::Point2d;
+
+// how the game's logical coordinate space maps onto the canvas. the canvas is scaled to fit
+// its container on screen, and its backing store has a pixel for every device pixel so
+// drawing stays sharp on high density displays
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+  // size of the logical coordinate space games draw in
+  width: f64,
+  height: f64,
+  // css pixels per logical unit
+  css_scale: f64,
+  // device pixels per css pixel
+  device_pixel_ratio: f64,
+}
+
+impl Viewport {
+  // the largest viewport that fits in a container of the given css size without changing
+  // the aspect ratio. an empty container leaves the canvas at its logical size
+  pub fn fit(width: f64, height: f64, container_width: f64, container_height: f64, device_pixel_ratio: f64) -> Self {
+    let css_scale = if container_width > 0.0 && container_height > 0.0 {
+      f64::min(container_width / width, container_height / height)
+    } else {
+      1.0
+    };
+    let device_pixel_ratio = if device_pixel_ratio > 0.0 { device_pixel_ratio } else { 1.0 };
+    Self { width, height, css_scale, device_pixel_ratio }
+  }
+
+  // size of the logical coordinate space
+  pub fn logical_size(&self) -> (f64, f64) {
+    (self.width, self.height)
+  }
+
+  // size of the canvas element on screen, in css pixels
+  pub fn css_size(&self) -> (f64, f64) {
+    (self.width * self.css_scale, self.height * self.css_scale)
+  }
+
+  // size of the canvas backing store, in device pixels
+  pub fn backing_size(&self) -> (u32, u32) {
+    let (width, height) = self.css_size();
+    ((width * self.device_pixel_ratio).round() as u32, (height * self.device_pixel_ratio).round() as u32)
+  }
+
+  // device pixels per logical unit, i.e. the transform to draw logical coordinates with
+  pub fn scale(&self) -> f64 {
+    self.css_scale * self.device_pixel_ratio
+  }
+
+  // convert a point in css pixels relative to the canvas (e.g. a pointer event's offset)
+  // into logical coordinates
+  pub fn to_logical(&self, p: &Point2d) -> Point2d {
+    Point2d { x: p.x / self.css_scale, y: p.y / self.css_scale }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn fits_container_and_maps_pointer_back() {
+    // a phone in portrait: 390 css pixels wide at 3 device pixels each
+    let viewport = Viewport::fit(800.0, 600.0, 390.0, 700.0, 3.0);
+    assert_eq!(viewport.css_size(), (390.0, 292.5));
+    assert_eq!(viewport.backing_size(), (1170, 878));
+    assert!((viewport.scale() - 1.4625).abs() < 1e-9);
+    assert_eq!(viewport.to_logical(&Point2d { x: 195.0, y: 146.25 }), Point2d { x: 400.0, y: 300.0 });
+
+    let detached = Viewport::fit(800.0, 600.0, 0.0, 0.0, 0.0);
+    assert_eq!(detached.backing_size(), (800, 600));
+  }
+}
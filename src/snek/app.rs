@@ -0,0 +1,200 @@
+use async_trait::async_trait;
+use crate::engine::{EngineConfig, EngineError, Game, GameEvent, KeyState, Renderer, SceneStack};
+use crate::snek::game::{MIN_HEIGHT, MIN_WIDTH, SnekGame};
+use crate::snek::save;
+use crate::snek::scenes::{PauseScene, PlayScene, TitleScene};
+use crate::snek::tuning::Tuning;
+
+// the game the engine runs: a run of SnekGame and the screens around it
+pub struct SnekApp {
+  game: SnekGame,
+  scenes: SceneStack<SnekGame>,
+  // the run is a replay being played back, not the player's own
+  playback: bool,
+}
+
+impl SnekApp {
+  // a run saved by an earlier session can be continued from the title screen, unless a
+  // replay is being played back
+  pub fn new(seed: u64, config: &EngineConfig, playback: bool) -> Self {
+    Self::with_saved(seed, config, playback, save::load(config))
+  }
+
+  // a replay starts from a new run, so continuing the player's saved run would play it
+  // back wrongly
+  fn with_saved(seed: u64, config: &EngineConfig, playback: bool, saved: Option<SnekGame>) -> Self {
+    let saved = if playback { None } else { saved };
+    Self {
+      game: SnekGame::new(seed, config),
+      scenes: SceneStack::new(Box::new(TitleScene::new(saved, playback))),
+      playback,
+    }
+  }
+
+  // what init reports: settings the engine can't run with, a canvas too small to play on
+  // or invalid tuning
+  fn check(config: &EngineConfig) -> Result<(), EngineError> {
+    config.validate()?;
+    if config.width < MIN_WIDTH || config.height < MIN_HEIGHT {
+      return Err(EngineError::InitFailed(format!("the canvas must be at least {MIN_WIDTH}x{MIN_HEIGHT}")));
+    }
+    Tuning::from_config(config).map_err(EngineError::InitFailed)?;
+    Ok(())
+  }
+}
+
+#[async_trait(?Send)]
+impl Game for SnekApp {
+  async fn init(&self, config: &EngineConfig) -> Result<Box<dyn Game>, EngineError> {
+    Self::check(config)?;
+    Ok(Box::new(Self::new(self.game.seed(), config, self.playback)))
+  }
+
+  fn seed(&self) -> u64 {
+    self.game.seed()
+  }
+
+  fn update(&mut self, key_state: &KeyState) {
+    self.scenes.update(&mut self.game, key_state);
+  }
+
+  fn draw(&self, renderer: &dyn Renderer, alpha: f64) {
+    self.scenes.draw(&self.game, renderer, alpha);
+  }
+
+  // a restart skips the title and goes straight to the new run, which is the player's
+  // own even if a replay was being played back
+  fn restart(&mut self, seed: u64) {
+    save::clear();
+    self.playback = false;
+    self.game.restart(seed);
+    self.scenes = SceneStack::new(Box::new(PlayScene::new(false)));
+  }
+
+  fn pause_changed(&mut self, paused: bool) {
+    if paused {
+      self.scenes.push(Box::new(PauseScene));
+    } else {
+      self.scenes.pop();
+    }
+  }
+
+  // a replay being played back isn't saved over the player's own run
+  fn suspend(&mut self) {
+    if self.game.in_progress() && !self.playback {
+      save::save(&self.game);
+    }
+  }
+
+  fn events(&mut self) -> Vec<GameEvent> {
+    self.game.events()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::{Action, DrawCommand, InputSource, RecordingRenderer, Replay, ReplayPlayer};
+
+  fn texts(app: &SnekApp) -> Vec<String> {
+    let renderer = RecordingRenderer::new();
+    app.draw(&renderer, 1.0);
+    renderer.take().into_iter().filter_map(|command| match command {
+      DrawCommand::Text { text, .. } => Some(text),
+      _ => None,
+    }).collect()
+  }
+
+  #[test]
+  fn rejects_configs_it_cannot_play_in() {
+    assert_eq!(SnekApp::check(&EngineConfig::default()), Ok(()));
+    assert!(SnekApp::check(&EngineConfig { tick_rate: 0, ..EngineConfig::default() }).is_err());
+    assert!(SnekApp::check(&EngineConfig { width: 100, height: 100, ..EngineConfig::default() }).is_err());
+  }
+
+  #[test]
+  fn continuing_a_saved_run_reports_its_seed() {
+    let config = EngineConfig::default();
+    let mut app = SnekApp {
+      scenes: SceneStack::new(Box::new(TitleScene::new(Some(SnekGame::new(9, &config)), false))),
+      ..SnekApp::new(0, &config, false)
+    };
+    let mut confirm = KeyState::new();
+    confirm.press(Action::Confirm);
+    app.update(&confirm);
+    assert_eq!(app.seed(), 9);
+    assert_eq!(app.events(), vec![GameEvent::RunRestored { seed: 9 }]);
+  }
+
+  #[test]
+  fn playing_back_a_replay_ignores_the_saved_run() {
+    let config = EngineConfig::default();
+    let mut confirm = KeyState::new();
+    confirm.press(Action::Confirm);
+    let mut left = KeyState::new();
+    left.press(Action::Left);
+    let steps = [vec![confirm.clone()], vec![KeyState::new(); 30], vec![left; 10], vec![KeyState::new(); 100]].concat();
+
+    let mut app = SnekApp::new(4, &config, false);
+    let mut replay = Replay::new(app.seed());
+    for key_state in &steps {
+      app.update(key_state);
+      replay.record(key_state);
+    }
+
+    // the player has a run of their own saved, which confirm would continue
+    let mut saved = SnekGame::new(9, &config);
+    saved.update(&confirm);
+    let mut played = SnekApp::with_saved(replay.seed(), &config, true, Some(saved));
+    let mut player = ReplayPlayer::new(replay);
+    for _ in &steps {
+      played.update(&player.process());
+    }
+    assert_eq!(played.seed(), 4);
+    assert_eq!(save::to_json(&played.game), save::to_json(&app.game));
+  }
+
+  #[test]
+  fn goes_from_title_to_play_to_results() {
+    let mut app = SnekApp::new(0, &EngineConfig::default(), false);
+    // the title is drawn over the field, which labels the exit
+    let field = texts(&SnekApp { scenes: SceneStack::new(Box::new(PlayScene::new(false))), ..SnekApp::new(0, &EngineConfig::default(), false) });
+    assert_eq!(texts(&app), [field.clone(), vec!["snek".to_string(), "press enter to start".to_string()]].concat());
+
+    let mut confirm = KeyState::new();
+    confirm.press(Action::Confirm);
+    app.update(&confirm);
+    assert_eq!(texts(&app), field);
+
+    app.pause_changed(true);
+    assert_eq!(texts(&app), [field.clone(), vec!["PAUSED".to_string()]].concat());
+    app.pause_changed(false);
+
+    // head straight up into the boundary
+    let mut up = KeyState::new();
+    up.press(Action::Up);
+    for _ in 0..100 {
+      app.update(&up);
+    }
+    // there's no storage outside the browser, so the table only holds this run
+    let results = texts(&app);
+    assert_eq!(results[..4], ["GAME OVER", "score 1 in 0:01", "press R to play again", "high scores"]);
+    assert_eq!(results.len(), 5);
+
+    app.restart(1);
+    assert_eq!(app.seed(), 1);
+    assert_eq!(texts(&app), field);
+  }
+
+  #[test]
+  fn a_played_back_run_is_not_a_high_score() {
+    let mut app = SnekApp::new(0, &EngineConfig::default(), true);
+    let mut up = KeyState::new();
+    up.press(Action::Up);
+    for _ in 0..100 {
+      app.update(&up);
+    }
+    // the table is the viewer's, which has nothing in it outside the browser
+    assert_eq!(texts(&app), ["GAME OVER", "score 1 in 0:01", "press R to play again", "high scores"]);
+  }
+}
@@ -0,0 +1,109 @@
+use crate::engine::{KeyState, Point2d, Rect};
+use crate::snek::entity::Direction;
+use crate::snek::game::SnekGame;
+use crate::snek::placement::distance_to_path;
+
+// how close (in pixels) the head is allowed to get to the boundary before turning away
+const CLEARANCE: f64 = 30.0;
+// how far ahead (in pixels) directions are compared when heading for a target
+const LOOK_AHEAD: f64 = 20.0;
+// how far (in pixels) to go after a turn before turning again. turning back any sooner
+// runs into the snek's own body
+const MIN_SEGMENT: f64 = 15.0;
+// how close (in pixels) the point LOOK_AHEAD in front of the head may come to the body
+const BODY_CLEARANCE: f64 = 15.0;
+
+// a simple computer player for balance testing. it heads for the nearest pill, or for the
+// exit once the boundary has grown to reach it, and turns before it runs into the boundary.
+// it doesn't look out for enemy sneks
+pub fn autopilot(game: &SnekGame) -> KeyState {
+  let snek = game.snek();
+  let head = snek.path()[snek.path().len() - 1].clone();
+  let heading = snek.direction();
+  // the area the middle of the head can be in without touching the boundary
+  let Rect { x, y, width, height } = game.boundary();
+  let inside = Rect::new(x + 10.0, y + 10.0, width - 20.0, height - 20.0);
+  let exit = game.exit().rect();
+  let exit_open = inside.x + inside.width > exit.x;
+
+  // with nothing to head for, keep going straight
+  let target = if exit_open {
+    center(&exit)
+  } else {
+    game.pills().iter()
+      .map(|pill| pill.position.clone())
+      .min_by(|a, b| distance(&head, a).total_cmp(&distance(&head, b)))
+      .unwrap_or_else(|| ahead(&head, heading))
+  };
+
+  let corner = &snek.path()[snek.path().len() - 2];
+  let options = if distance(&head, corner) < MIN_SEGMENT {
+    vec![heading]
+  } else {
+    vec![heading, heading.turns()[0], heading.turns()[1]]
+  };
+  let body = &snek.path()[..snek.path().len() - 1];
+  let safe = |d: &Direction| {
+    let in_bounds = clearance(&head, *d, &inside) > CLEARANCE || (exit_open && *d == Direction::Right);
+    in_bounds && distance_to_path(&ahead(&head, *d), body) >= BODY_CLEARANCE
+  };
+  let direction = options.iter()
+    .filter(|d| safe(d))
+    .min_by(|a, b| distance(&ahead(&head, **a), &target).total_cmp(&distance(&ahead(&head, **b), &target)))
+    // boxed in, so take whichever way has the most room
+    .or_else(|| options.iter().max_by(|a, b| clearance(&head, **a, &inside).total_cmp(&clearance(&head, **b, &inside))))
+    .copied()
+    .unwrap_or(heading);
+
+  let mut key_state = KeyState::new();
+  key_state.press(direction.action());
+  key_state
+}
+
+fn center(rect: &Rect) -> Point2d {
+  Point2d { x: rect.x + rect.width / 2.0, y: rect.y + rect.height / 2.0 }
+}
+
+fn distance(a: &Point2d, b: &Point2d) -> f64 {
+  (a.x - b.x).abs() + (a.y - b.y).abs()
+}
+
+fn ahead(p: &Point2d, direction: Direction) -> Point2d {
+  match direction {
+    Direction::Up    => Point2d { x: p.x, y: p.y - LOOK_AHEAD },
+    Direction::Down  => Point2d { x: p.x, y: p.y + LOOK_AHEAD },
+    Direction::Left  => Point2d { x: p.x - LOOK_AHEAD, y: p.y },
+    Direction::Right => Point2d { x: p.x + LOOK_AHEAD, y: p.y },
+  }
+}
+
+// room between p and the edge of rect, heading in direction
+fn clearance(p: &Point2d, direction: Direction, rect: &Rect) -> f64 {
+  match direction {
+    Direction::Up    => p.y - rect.y,
+    Direction::Down  => rect.y + rect.height - p.y,
+    Direction::Left  => p.x - rect.x,
+    Direction::Right => rect.x + rect.width - p.x,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::EngineConfig;
+
+  #[test]
+  fn keeps_away_from_the_boundary() {
+    let survived = (0..10).map(|seed| {
+      let mut game = SnekGame::new(seed, &EngineConfig::default());
+      for _ in 0..3000 {
+        let key_state = autopilot(&game);
+        game.update(&key_state);
+      }
+      game.score().elapsed()
+    });
+    // the snek starts in a 200x200 boundary and only lasts a few seconds without turning.
+    // how long it lasts otherwise depends on the pills, so look at the average over a few runs
+    assert!(survived.sum::<f64>() / 10.0 > 20.0);
+  }
+}
@@ -195,7 +195,7 @@ impl Collision<Vec<Pill>> for Snek {
     };
 
     for (i, pill) in pills.iter().enumerate() {
-      if pill.contains(&head) || pill.contains(&h1) || pill.contains(&h2) {
+      if pill.contains(head) || pill.contains(&h1) || pill.contains(&h2) {
         return Some(i);
       }
     }
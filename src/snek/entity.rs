@@ -1,25 +1,88 @@
-use crate::constants::*;
-use crate::engine::{Key,KeyState,Point2d,Rect,Renderer};
-use rand;
-use wasm_bindgen::prelude::*;
+use crate::engine::{Action,KeyState,Point2d,Rect,Renderer};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
-#[derive(Clone,Copy,Debug,Eq,Ord,PartialEq,PartialOrd)]
+// turns waiting to be taken beyond this are dropped
+const MAX_QUEUED_TURNS: usize = 3;
+// width and height of the boundary at the start of a run
+const BOUNDARY_SIZE: f64 = 200.0;
+// length of a new snek, from its tail to its head
+pub const START_LENGTH: f64 = 20.0;
+
+#[derive(Clone,Copy,Debug,Deserialize,Eq,Ord,PartialEq,PartialOrd,Serialize)]
 pub enum Direction { Left, Right, Up, Down }
 
+impl Direction {
+  fn from_action(action: Action) -> Option<Self> {
+    match action {
+      Action::Up    => Some(Direction::Up),
+      Action::Down  => Some(Direction::Down),
+      Action::Left  => Some(Direction::Left),
+      Action::Right => Some(Direction::Right),
+      _ => None,
+    }
+  }
+
+  pub fn action(self) -> Action {
+    match self {
+      Direction::Up    => Action::Up,
+      Direction::Down  => Action::Down,
+      Direction::Left  => Action::Left,
+      Direction::Right => Action::Right,
+    }
+  }
+
+  // the two directions a snek heading this way can turn to
+  pub fn turns(self) -> [Direction; 2] {
+    match self {
+      Direction::Up | Direction::Down => [Direction::Left, Direction::Right],
+      Direction::Left | Direction::Right => [Direction::Up, Direction::Down],
+    }
+  }
+
+  pub fn opposite(self) -> Direction {
+    match self {
+      Direction::Up    => Direction::Down,
+      Direction::Down  => Direction::Up,
+      Direction::Left  => Direction::Right,
+      Direction::Right => Direction::Left,
+    }
+  }
+
+  // a snek can only turn 90 degrees
+  fn is_turn_from(self, current: Direction) -> bool {
+    match self {
+      Direction::Up | Direction::Down => current == Direction::Left || current == Direction::Right,
+      Direction::Left | Direction::Right => current == Direction::Up || current == Direction::Down,
+    }
+  }
+}
+
 pub fn direction(key_state: &KeyState) -> Option<Direction> {
-  if key_state.is_pressed(Key::Up)    { return Some(Direction::Up); }
-  if key_state.is_pressed(Key::Down)  { return Some(Direction::Down); }
-  if key_state.is_pressed(Key::Left)  { return Some(Direction::Left); }
-  if key_state.is_pressed(Key::Right) { return Some(Direction::Right); }
-  return None;
+  if key_state.is_pressed(Action::Up)    { return Some(Direction::Up); }
+  if key_state.is_pressed(Action::Down)  { return Some(Direction::Down); }
+  if key_state.is_pressed(Action::Left)  { return Some(Direction::Left); }
+  if key_state.is_pressed(Action::Right) { return Some(Direction::Right); }
+  None
 }
 
-#[derive(Clone)]
+// a direction was pressed this step. unlike direction, this also sees a tap that was
+// released again before the step ran
+pub fn direction_pressed(key_state: &KeyState) -> bool {
+  key_state.presses().iter().any(|action| Direction::from_action(*action).is_some())
+}
+
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Snek {
   color: String,
   speed: f64, // pixels per second
   path: Vec<Point2d>,
   direction: Direction,
+  // turns requested by the player that have not been taken yet, oldest first
+  turns: VecDeque<Direction>,
+  // tail and head before the most recent update, for drawing between steps
+  previous: Option<(Point2d, Point2d)>,
 }
 
 impl Snek {
@@ -27,41 +90,40 @@ impl Snek {
     let mut path = Vec::new();
     let Point2d { x, y } = position.clone();
     match direction {
-      Direction::Up    => { path.push(Point2d { x, y: y+20.0 }); }
-      Direction::Down  => { path.push(Point2d { x, y: y-20.0 }); }
-      Direction::Left  => { path.push(Point2d { x: x+20.0, y }); }
-      Direction::Right => { path.push(Point2d { x: x-20.0, y }); }
+      Direction::Up    => { path.push(Point2d { x, y: y+START_LENGTH }); }
+      Direction::Down  => { path.push(Point2d { x, y: y-START_LENGTH }); }
+      Direction::Left  => { path.push(Point2d { x: x+START_LENGTH, y }); }
+      Direction::Right => { path.push(Point2d { x: x-START_LENGTH, y }); }
     }
     path.push(position);
-    Self { color, speed, path, direction }
+    Self { color, speed, path, direction, turns: VecDeque::new(), previous: None }
   }
 
   pub fn path(&self) -> &Vec<Point2d> { &self.path }
   pub fn direction(&self) -> Direction { self.direction }
+  pub fn speed(&self) -> f64 { self.speed }
 
-  pub fn update(&mut self, key_state: &KeyState) {
-    // handle changing directions
-    if let Some(d) = direction(key_state) {
-      match (d, self.direction) {
-        // ignore if the keypress is the same direction or opposite direction
-        (Direction::Left, Direction::Up) |
-        (Direction::Left, Direction::Down) |
-        (Direction::Right, Direction::Up) |
-        (Direction::Right, Direction::Down) |
-        (Direction::Up, Direction::Left) |
-        (Direction::Up, Direction::Right) |
-        (Direction::Down, Direction::Left) |
-        (Direction::Down, Direction::Right) => {
-          self.direction = d;
-          //log!("CHANGE DIRECTIONS {d:?}");
-          let current_pos = self.path[self.path.len() - 1].clone();
-          self.path.push(current_pos);
-        },
-        _ => {}
+  // advance the snek by one step of dt seconds
+  pub fn update(&mut self, key_state: &KeyState, dt: f64) {
+    self.remember_position();
+    // queue turns in the order the keys were pressed
+    for d in key_state.presses().iter().filter_map(|action| Direction::from_action(*action)) {
+      if self.turns.len() < MAX_QUEUED_TURNS {
+        self.turns.push_back(d);
+      }
+    }
+    // take at most one turn per step. queued turns that are the same or opposite
+    // direction by the time they come up are dropped
+    while let Some(d) = self.turns.pop_front() {
+      if d.is_turn_from(self.direction) {
+        self.direction = d;
+        let current_pos = self.path[self.path.len() - 1].clone();
+        self.path.push(current_pos);
+        break;
       }
     }
     // move the snek
-    let distance = self.speed * FRAME_LENGTH;
+    let distance = self.speed * dt;
     let i = self.path.len() - 1;
     // lengthen in the direction of movement
     match self.direction {
@@ -74,8 +136,28 @@ impl Snek {
     shorten_path(&mut self.path, distance*0.95);
   }
 
-  pub fn draw(&self, renderer: &Renderer) {
-    renderer.path(&self.path, &JsValue::from(&self.color), Some(10.0));
+  // draw the snek alpha of the way (0.0 to 1.0) from where it was before the last update
+  // to where it is now
+  pub fn draw(&self, renderer: &dyn Renderer, alpha: f64) {
+    renderer.path(&self.interpolated_path(alpha), &self.color, Some(10.0));
+  }
+
+  fn interpolated_path(&self, alpha: f64) -> Vec<Point2d> {
+    let mut path = self.path.clone();
+    if let Some((tail, head)) = &self.previous {
+      // the head always moves along the final segment, even right after a turn
+      let last = path.len() - 1;
+      path[last] = head.lerp(&path[last], alpha);
+      // the tail can be cut back past a corner, in which case it just snaps
+      if (tail.x == path[0].x && path[0].x == path[1].x) || (tail.y == path[0].y && path[0].y == path[1].y) {
+        path[0] = tail.lerp(&path[0], alpha);
+      }
+    }
+    path
+  }
+
+  fn remember_position(&mut self) {
+    self.previous = Some((self.path[0].clone(), self.path[self.path.len() - 1].clone()));
   }
 
   pub fn shorten(&mut self, percentage: f64) {
@@ -98,8 +180,8 @@ fn length(snek: &Snek) -> f64 {
   result
 }
 
-fn final_segment_length(path: &Vec<Point2d>) -> f64 {
-  let p0 = path.get(0).unwrap();
+fn final_segment_length(path: &[Point2d]) -> f64 {
+  let p0 = path.first().unwrap();
   let p1 = path.get(1).unwrap();
   (p0.x - p1.x).abs() + (p0.y - p1.y).abs()
 }
@@ -128,57 +210,74 @@ fn shorten_path(path: &mut Vec<Point2d>, amount: f64) {
   }
 }
 
+#[derive(Deserialize, Serialize)]
 pub struct Boundary {
-  rect: Rect
+  rect: Rect,
+  // the area the boundary can expand to fill
+  arena: Rect,
 }
 
 impl Boundary {
-  pub fn new() -> Self {
-    Self { rect: Rect::new(300.0, 200.0, 200.0, 200.0) }
+  // a square in the middle of the arena
+  pub fn new(arena: &Rect) -> Self {
+    let x = arena.x + arena.width / 2.0 - BOUNDARY_SIZE / 2.0;
+    let y = arena.y + arena.height / 2.0 - BOUNDARY_SIZE / 2.0;
+    Self { rect: Rect::new(x, y, BOUNDARY_SIZE, BOUNDARY_SIZE), arena: arena.clone() }
   }
 
   pub fn rect(&self) -> Rect {
     self.rect.clone()
   }
 
-  pub fn draw(&self, renderer: &Renderer) {
+  // how many pixels wider the boundary has grown since the start of the run
+  pub fn growth(&self) -> f64 {
+    self.rect.width - BOUNDARY_SIZE
+  }
+
+  pub fn draw(&self, renderer: &dyn Renderer) {
     renderer.rect(
       &self.rect,
-      Some(&JsValue::from("black")),
-      Some(&JsValue::from("red")),
+      Some("black"),
+      Some("red"),
       Some(10.0));
   }
 
-  pub fn expand(&mut self) {
+  // move each side out by amount, without leaving the arena
+  pub fn expand(&mut self, amount: f64) {
     let Rect { x, y, width, height } = self.rect.clone();
+    let arena = &self.arena;
     self.rect = Rect::new(
-      f64::max(x - 5.0, 5.0),
-      f64::max(y - 5.0, 5.0),
-      f64::min(width + 10.0, CANVAS_WIDTH as f64-10.0),
-      f64::min(height + 10.0, CANVAS_HEIGHT as f64-10.0));
+      f64::max(x - amount, arena.x + 5.0),
+      f64::max(y - amount, arena.y + 5.0),
+      f64::min(width + 2.0 * amount, arena.width - 10.0),
+      f64::min(height + 2.0 * amount, arena.height - 10.0));
   }
 
-  pub fn random_point(&self) -> Point2d {
+  pub fn random_point(&self, rng: &mut impl Rng) -> Point2d {
     let Rect { x, y, width, height } = self.rect.clone();
-    let x = x + 10.0 + (width-20.0)*rand::random::<f64>();
-    let y = y + 10.0 + (height-20.0)*rand::random::<f64>();
+    let x = x + 10.0 + (width-20.0)*rng.gen::<f64>();
+    let y = y + 10.0 + (height-20.0)*rng.gen::<f64>();
     Point2d { x, y }
   }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct AiSnek {
   snek: Snek,
   alive: bool,
-  frames_since_turn: u8,
+  // seconds since it last turned
+  time_since_turn: f64,
+  // seconds between turns
+  turn_every: f64,
 }
 
 impl AiSnek {
-  pub fn new(color: String, speed: f64, position: Point2d, direction: Direction) -> Self {
+  pub fn new(color: String, speed: f64, position: Point2d, direction: Direction, turn_every: f64) -> Self {
     Self {
       snek: Snek::new(color, speed, position, direction),
       alive: true,
-      frames_since_turn: 0,
+      time_since_turn: 0.0,
+      turn_every,
     }
   }
 
@@ -186,35 +285,45 @@ impl AiSnek {
     &self.snek
   }
 
+  pub fn is_alive(&self) -> bool {
+    self.alive
+  }
+
   pub fn die(&mut self) {
     self.alive = false;
   }
 
-  pub fn draw(&self, renderer: &Renderer) {
-    self.snek.draw(renderer);
+  pub fn draw(&self, renderer: &dyn Renderer, alpha: f64) {
+    self.snek.draw(renderer, alpha);
   }
 
-  pub fn update(&mut self) {
-    if !self.alive { return; }
+  pub fn update(&mut self, rng: &mut impl Rng, dt: f64) {
+    if !self.alive {
+      // a dead snek stays where it is
+      self.snek.previous = None;
+      return;
+    }
+    self.snek.remember_position();
 
     // TODO decision to turn
-    self.frames_since_turn += 1;
-    if self.frames_since_turn == 50 {
-      self.frames_since_turn = 0;
+    // turn on the step nearest turn_every, whatever the tick rate
+    self.time_since_turn += dt;
+    if self.time_since_turn + dt / 2.0 >= self.turn_every {
+      self.time_since_turn = 0.0;
       let current_pos = self.snek.path[self.snek.path.len() - 1].clone();
       self.snek.path.push(current_pos);
       match self.snek.direction {
         Direction::Up | Direction::Down => {
-          self.snek.direction = if rand::random() { Direction::Left } else { Direction::Right };
+          self.snek.direction = if rng.gen() { Direction::Left } else { Direction::Right };
         },
         Direction::Left | Direction::Right => {
-          self.snek.direction = if rand::random() { Direction::Up } else { Direction::Down };
+          self.snek.direction = if rng.gen() { Direction::Up } else { Direction::Down };
         }
       }
     }
 
     // move the snek
-    let distance = self.snek.speed * FRAME_LENGTH;
+    let distance = self.snek.speed * dt;
     let i = self.snek.path.len() - 1;
     // lengthen in the direction of movement
     match self.snek.direction {
@@ -228,29 +337,107 @@ impl AiSnek {
   }
 }
 
+#[derive(Deserialize, Serialize)]
 pub struct Exit {
   rect: Rect,
 }
 
 impl Exit {
-  pub fn new() -> Self {
-    Self { rect: Rect::new(780.0, 260.0, 20.0, 80.0) }
+  // halfway down the right edge of the arena
+  pub fn new(arena: &Rect) -> Self {
+    let x = arena.x + arena.width - 20.0;
+    let y = arena.y + arena.height / 2.0 - 40.0;
+    Self { rect: Rect::new(x, y, 20.0, 80.0) }
   }
 
-  pub fn draw(&self, renderer: &Renderer) {
+  pub fn draw(&self, renderer: &dyn Renderer) {
     renderer.rect(
       &self.rect,
-      Some(&JsValue::from("white")),
+      Some("white"),
       None,
       None);
-    renderer.text("E", "black", 16, 783.0, 278.0);
-    renderer.text("X", "black", 16, 783.0, 298.0);
-    renderer.text("I", "black", 16, 787.0, 318.0);
-    renderer.text("T", "black", 16, 783.0, 338.0);
+    let Rect { x, y, .. } = self.rect;
+    renderer.text("E", "black", 16, x + 3.0, y + 18.0);
+    renderer.text("X", "black", 16, x + 3.0, y + 38.0);
+    renderer.text("I", "black", 16, x + 7.0, y + 58.0);
+    renderer.text("T", "black", 16, x + 3.0, y + 78.0);
   }
 
   pub fn contains(&self, p: &Point2d) -> bool {
     self.rect.contains(p)
   }
+
+  pub fn rect(&self) -> Rect {
+    self.rect.clone()
+  }
 }
 
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::SeedableRng;
+  use rand_pcg::Pcg32;
+
+  #[test]
+  fn quick_turns_are_taken_on_consecutive_steps() {
+    let mut snek = Snek::new("white".to_string(), 60.0, Point2d { x: 400.0, y: 300.0 }, Direction::Up);
+    let mut key_state = KeyState::new();
+    key_state.press(Action::Down); // opposite direction, dropped
+    key_state.press(Action::Left);
+    key_state.press(Action::Up);
+
+    snek.update(&key_state, 1.0 / 30.0);
+    assert_eq!(snek.direction(), Direction::Left);
+    snek.update(&KeyState::new(), 1.0 / 30.0);
+    assert_eq!(snek.direction(), Direction::Up);
+    snek.update(&KeyState::new(), 1.0 / 30.0);
+    assert_eq!(snek.direction(), Direction::Up);
+    assert_eq!(snek.path().len(), 4);
+  }
+
+  #[test]
+  fn draws_between_the_last_two_steps() {
+    let mut snek = Snek::new("white".to_string(), 60.0, Point2d { x: 400.0, y: 300.0 }, Direction::Up);
+    assert_eq!(snek.interpolated_path(0.5), snek.path().clone());
+
+    snek.update(&KeyState::new(), 1.0 / 30.0);
+    let path = snek.interpolated_path(0.5);
+    assert_eq!(path[0], Point2d { x: 400.0, y: 320.0 - 0.95 });
+    assert_eq!(path[1], Point2d { x: 400.0, y: 299.0 });
+    assert_eq!(snek.interpolated_path(1.0), snek.path().clone());
+  }
+
+  #[test]
+  fn same_seed_gives_same_ai_snek_moves() {
+    let run = |seed: u64| {
+      let mut rng = Pcg32::seed_from_u64(seed);
+      let mut snek = AiSnek::new("red".to_string(), 40.0, Point2d { x: 400.0, y: 300.0 }, Direction::Up, 1.67);
+      for _ in 0..500 {
+        snek.update(&mut rng, 1.0 / 30.0);
+      }
+      snek.get().path().clone()
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+  }
+
+  #[test]
+  fn ai_sneks_turn_at_the_same_times_at_any_tick_rate() {
+    let run = |tick_rate: u32| {
+      let mut rng = Pcg32::seed_from_u64(7);
+      let mut snek = AiSnek::new("red".to_string(), 40.0, Point2d { x: 400.0, y: 300.0 }, Direction::Up, 1.67);
+      let mut turns = Vec::new();
+      for step in 1..=10 * tick_rate {
+        let direction = snek.get().direction();
+        snek.update(&mut rng, 1.0 / tick_rate as f64);
+        if snek.get().direction() != direction {
+          turns.push((step as f64 / tick_rate as f64 * 10.0).round() / 10.0);
+        }
+      }
+      turns
+    };
+    assert_eq!(run(30), vec![1.7, 3.3, 5.0, 6.7, 8.3, 10.0]);
+    assert_eq!(run(60), run(30));
+  }
+}
@@ -1,13 +1,36 @@
-use async_trait::async_trait;
-use crate::constants::*;
-use crate::engine::{Game, KeyState, Point2d, Rect, Renderer};
+use crate::engine::{EngineConfig, GameEvent, KeyState, Point2d, Rect, Renderer};
 use crate::snek::collision::{Collision};
-use crate::snek::entity::{AiSnek, Boundary, Direction, direction, Exit, Snek};
+use crate::snek::entity::{AiSnek, Boundary, Direction, direction, direction_pressed, Exit, Snek};
 use crate::snek::pill::{Pill,PillType};
-use rand;
-use wasm_bindgen::prelude::*;
+use crate::snek::placement::Field;
+use crate::snek::score::{Award, format_time, Score, speed_multiplier};
+use crate::snek::spawn::SpawnTable;
+use crate::snek::tuning::Tuning;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
 
+// the smallest canvas with room for the starting boundary, the exit beside it and the hud
+// above it
+pub const MIN_WIDTH: u32 = 260;
+pub const MIN_HEIGHT: u32 = 280;
+
+// the simulation of a run. the screens around it (title, pause, results) are scenes in
+// SnekApp. serializable so a run in progress can be saved and resumed
+#[derive(Deserialize, Serialize)]
 pub struct SnekGame {
+  // every random decision in the simulation is drawn from rng, so a seed fully
+  // determines a run for a given sequence of inputs
+  seed: u64,
+  rng: Pcg32,
+  // kept to build the game again on restart
+  config: EngineConfig,
+  tuning: Tuning,
+  // the whole canvas
+  bounds: Rect,
+  // seconds of game time per update
+  step_length: f64,
   ready: bool,
   game_over: bool,
   win: bool,
@@ -16,156 +39,376 @@ pub struct SnekGame {
   snek: Snek,
   boundary: Boundary,
   pills: Vec<Pill>,
-  frames_since_pill_spawn: u64,
+  // seconds since a pill last spawned
+  time_since_pill_spawn: f64,
+  spawn_table: SpawnTable,
   enemy_sneks: Vec<AiSnek>,
+  score: Score,
+  // how many of each type of pill the player has eaten
+  #[serde(default)]
+  pills_eaten: BTreeMap<PillType, u32>,
+  // raised since the engine last asked for them
+  #[serde(skip)]
+  events: Vec<GameEvent>,
 }
 
 impl SnekGame {
-  pub fn new() -> Self {
+  // a config with invalid tuning gets the default tuning. SnekApp::init reports the error
+  pub fn new(seed: u64, config: &EngineConfig) -> Self {
+    let tuning = Tuning::from_config(config).unwrap_or_default();
+    let bounds = config.bounds();
+    let center = Point2d { x: bounds.width / 2.0, y: bounds.height / 2.0 };
     Self {
+      seed,
+      rng: Pcg32::seed_from_u64(seed),
+      config: config.clone(),
+      step_length: config.step_length(),
       ready: false,
       game_over: false,
       win: false,
       frame_number: 0,
-      exit: Exit::new(),
+      exit: Exit::new(&bounds),
       snek: Snek::new("white".to_string(),
-                      60.0,
-                      Point2d { x: 400.0, y: 300.0 },
+                      tuning.player.speed,
+                      center,
                       Direction::Up),
-      boundary: Boundary::new(),
+      boundary: Boundary::new(&bounds),
+      bounds,
       pills: Vec::new(),
-      frames_since_pill_spawn: 0,
+      time_since_pill_spawn: 0.0,
+      spawn_table: SpawnTable::new(tuning.pills.spawn_table.clone()),
       enemy_sneks: Vec::new(),
+      score: Score::new(),
+      pills_eaten: BTreeMap::new(),
+      events: Vec::new(),
+      tuning,
     }
   }
-}
 
-#[async_trait(?Send)]
-impl Game for SnekGame {
-  async fn init(&self) -> Result<Box<dyn Game>, ()> {
-    Ok(Box::new(Self::new()))
+  pub fn seed(&self) -> u64 {
+    self.seed
+  }
+
+  pub fn is_over(&self) -> bool {
+    self.game_over
+  }
+
+  // the player has started moving and the run isn't over yet
+  pub fn in_progress(&self) -> bool {
+    self.ready && !self.game_over
+  }
+
+  pub fn won(&self) -> bool {
+    self.win
+  }
+
+  pub fn score(&self) -> &Score {
+    &self.score
+  }
+
+  pub fn pills_eaten(&self, pill_type: PillType) -> u32 {
+    self.pills_eaten.get(&pill_type).copied().unwrap_or(0)
+  }
+
+  // the area the player can move in
+  pub fn boundary(&self) -> Rect {
+    self.boundary.rect()
+  }
+
+  pub(crate) fn snek(&self) -> &Snek {
+    &self.snek
+  }
+
+  pub(crate) fn pills(&self) -> &[Pill] {
+    &self.pills
+  }
+
+  pub(crate) fn exit(&self) -> &Exit {
+    &self.exit
   }
 
-  fn update(&mut self, key_state: &KeyState) {
+  // everything scores more the faster the player is going
+  fn multiplier(&self) -> f64 {
+    speed_multiplier(self.snek.speed(), self.tuning.player.speed)
+  }
+
+  fn end(&mut self, win: bool) {
+    self.game_over = true;
+    self.win = win;
+    self.events.push(GameEvent::GameOver { won: win });
+  }
+
+  pub fn update(&mut self, key_state: &KeyState) {
+    let score = self.score.total();
+    self.step(key_state);
+    if self.score.total() != score {
+      self.events.push(GameEvent::ScoreChanged { score: self.score.total() });
+    }
+  }
+
+  fn step(&mut self, key_state: &KeyState) {
     // ---------------------------------------------------------------------
     // bookkeeping
     self.frame_number += 1;
-    self.frames_since_pill_spawn += 1;
+    self.time_since_pill_spawn += self.step_length;
     // ready flag prevents game from starting until user provides an input
-    if let Some(_) = direction(key_state) { self.ready = true; }
+    if direction(key_state).is_some() || direction_pressed(key_state) { self.ready = true; }
     if !self.ready || self.game_over { return; }
+    self.score.survive(self.step_length, self.multiplier());
 
     // ---------------------------------------------------------------------
     // check for collisions
     if self.snek.colliding(&self.exit) {
-      self.game_over = true;
-      self.win = true;
+      self.score.award(Award::Exit, self.multiplier());
+      self.end(true);
       return;
     }
-    if self.snek.colliding(&()) || self.snek.colliding(&self.boundary) {
-      self.game_over = true;
+    if self.snek.colliding(&()) || self.snek.colliding(&self.boundary)
+       || self.enemy_sneks.iter().any(|enemy_snek| self.snek.colliding(enemy_snek)) {
+      self.end(false);
       return;
     }
-    for enemy_snek in &self.enemy_sneks {
-      if self.snek.colliding(enemy_snek) {
-        self.game_over = true;
-        return;
-      }
-    }
 
-    // if an enemy snek hits something, it "dies", ie. stops moving
+    // if an enemy snek hits something, it "dies", ie. stops moving. each death records
+    // whether the enemy ran into the player
     let mut deads = Vec::new();
     for i in 0..self.enemy_sneks.len() {
       let enemy_snek = &self.enemy_sneks[i];
 
+      if enemy_snek.colliding(&self.snek) {        // collides with player
+        deads.push((i, true));
+        continue;
+      }
+
       if enemy_snek.colliding(&())                 // collides with self
-         || enemy_snek.colliding(&self.snek)       // collides with player
          || enemy_snek.colliding(&self.boundary) { // collides with boundary
-        deads.push(i);
+        deads.push((i, false));
         continue;
       }
 
       for j in 0..self.enemy_sneks.len() {
         if i != j && enemy_snek.colliding(&self.enemy_sneks[j]) {
-          deads.push(i);
+          deads.push((i, false));
           continue;
         }
       }
     }
-    for i in deads {
-      self.enemy_sneks[i].die();
+    for (i, by_player) in deads {
+      if self.enemy_sneks[i].is_alive() {
+        self.enemy_sneks[i].die();
+        // only running into the player's body counts as a kill
+        if by_player {
+          self.score.award(Award::EnemyKilled, self.multiplier());
+        }
+      }
     }
 
     // check for pill collisions
     if let Some(i) = self.snek.colliding(&self.pills) {
-      log!("collided with pill {i:?}");
       let pill = self.pills.remove(i);
+      self.score.award(Award::Pill, self.multiplier());
+      *self.pills_eaten.entry(pill.pill_type).or_default() += 1;
       match pill.pill_type {
-        PillType::ExpandBoundary => { self.boundary.expand(); },
-        PillType::ShortenSnek => { self.snek.shorten(0.1); },
+        PillType::ExpandBoundary => { self.boundary.expand(self.tuning.boundary.expand_by); },
+        PillType::ShortenSnek => { self.snek.shorten(self.tuning.player.shorten_by); },
         PillType::SpawnEnemySnek => {
-          self.enemy_sneks.push(AiSnek::new("red".to_string(),
-                                            40.0,
-                                            self.boundary.random_point(),
-                                            Direction::Up));
+          // if there's nowhere fair to put it, the enemy doesn't appear
+          let field = Field::new(&self.boundary, &self.snek, &self.enemy_sneks, &self.pills);
+          if let Some((position, direction)) = field.enemy_start(&mut self.rng, &self.tuning.placement) {
+            self.enemy_sneks.push(AiSnek::new("red".to_string(),
+                                              self.tuning.enemy.speed,
+                                              position,
+                                              direction,
+                                              self.tuning.enemy.turn_every));
+          }
         }
-        PillType::IncreaseSpeed => { self.snek.increase_speed(5.0); }
+        PillType::IncreaseSpeed => { self.snek.increase_speed(self.tuning.player.speed_boost); }
       }
     }
 
     // ---------------------------------------------------------------------
     // update game state
-    self.snek.update(key_state);
+    self.snek.update(key_state, self.step_length);
 
     for snek in &mut self.enemy_sneks {
-      snek.update();
+      snek.update(&mut self.rng, self.step_length);
     }
 
-    // maybe spawn a pill
-    if rand::random::<f64>() < 0.0001 * self.frames_since_pill_spawn as f64 {
-      let Point2d { x, y } = self.boundary.random_point();
-      let n = rand::random::<u8>() % 6;
-      let pill_type = match n {
-        0 | 1 | 2 => PillType::ExpandBoundary,
-        3 => PillType::ShortenSnek,
-        4 => PillType::SpawnEnemySnek,
-        5 => PillType::IncreaseSpeed,
-        _ => unreachable!(),
-      };
-      let pill = Pill::new(pill_type, x, y);
-      self.pills.push(pill);
-      self.frames_since_pill_spawn = 0;
+    // top up any type of pill below its minimum, otherwise maybe spawn one
+    let elapsed = self.score.elapsed();
+    let mut on_field = BTreeMap::new();
+    for pill in &self.pills {
+      *on_field.entry(pill.pill_type).or_default() += 1;
     }
-  }
-
-  fn draw(&self, renderer: &Renderer) {
-    // clear the background and draw the border
-    renderer.clear();
-    renderer.rect(
-      &Rect::new(0.0, 0.0, CANVAS_WIDTH as f64, CANVAS_HEIGHT as f64),
-      None,
-      Some(&JsValue::from("red")),
-      Some(5.0));
-
-    if self.game_over {
-      renderer.text("GAME OVER", "red", 30, 280.0, 320.0);
-      if self.win {
-        renderer.text("you have won", "red", 20, 320.0, 360.0);
+    let pill_type = match self.spawn_table.below_min(elapsed, &on_field) {
+      Some(pill_type) => Some(pill_type),
+      // pills spawn at a rate per second that grows the longer it's been since the last one
+      None if self.rng.gen::<f64>() < self.tuning.pills.spawn_rate_growth * self.time_since_pill_spawn * self.step_length => {
+        self.spawn_table.choose(&mut self.rng, elapsed, self.boundary.growth(), &on_field)
+      },
+      None => None,
+    };
+    if let Some(pill_type) = pill_type {
+      // if there's nowhere fair to put it, try again on a later step
+      let field = Field::new(&self.boundary, &self.snek, &self.enemy_sneks, &self.pills);
+      if let Some(Point2d { x, y }) = field.pill_position(&mut self.rng, &self.tuning.placement) {
+        self.pills.push(Pill::new(pill_type, x, y));
+        self.spawn_table.spawned(pill_type, elapsed);
+        self.time_since_pill_spawn = 0.0;
       }
-      return;
     }
+  }
 
+  pub fn draw(&self, renderer: &dyn Renderer, alpha: f64) {
+    self.draw_background(renderer);
     self.boundary.draw(renderer);
     self.exit.draw(renderer);
-    self.snek.draw(renderer);
+    self.snek.draw(renderer, alpha);
 
     for snek in &self.enemy_sneks {
-      snek.draw(renderer);
+      snek.draw(renderer, alpha);
     }
 
     for pill in &self.pills {
       pill.draw(renderer);
     }
+
+    self.draw_hud(renderer);
+  }
+
+  // score, speed and time survived along the top of the canvas
+  fn draw_hud(&self, renderer: &dyn Renderer) {
+    let hud = format!("score {}   speed {:.0}   time {}",
+                      self.score.total(),
+                      self.snek.speed(),
+                      format_time(self.score.elapsed()));
+    renderer.text(&hud, "white", 12, 20.0, 30.0);
+  }
+
+  // clear the background and draw the border
+  pub fn draw_background(&self, renderer: &dyn Renderer) {
+    renderer.clear();
+    renderer.rect(
+      &self.bounds,
+      None,
+      Some("red"),
+      Some(5.0));
+  }
+
+  // the whole canvas
+  pub fn bounds(&self) -> &Rect {
+    &self.bounds
+  }
+
+  // the settings the run was created with
+  pub fn config(&self) -> &EngineConfig {
+    &self.config
+  }
+
+  // carry on with a run saved in an earlier session instead of this one
+  pub fn restore(&mut self, saved: SnekGame) {
+    *self = saved;
+    self.events.push(GameEvent::RunRestored { seed: self.seed });
+  }
+
+  // start over from the beginning with a new seed
+  pub fn restart(&mut self, seed: u64) {
+    *self = Self::new(seed, &self.config);
+  }
+
+  // events raised since the last call
+  pub fn events(&mut self) -> Vec<GameEvent> {
+    std::mem::take(&mut self.events)
   }
 }
 
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::{Action, DrawCommand, RecordingRenderer};
+
+  #[test]
+  fn first_frame_draws_the_playing_field() {
+    let game = SnekGame::new(0, &EngineConfig::default());
+    let renderer = RecordingRenderer::new();
+    game.draw(&renderer, 1.0);
+
+    let commands = renderer.take();
+    assert_eq!(commands[0], DrawCommand::Clear);
+    assert!(commands.contains(&DrawCommand::Rect {
+      rect: Rect::new(300.0, 200.0, 200.0, 200.0),
+      fill_style: Some("black".to_string()),
+      stroke_style: Some("red".to_string()),
+      line_width: Some(10.0),
+    }));
+    assert!(commands.contains(&DrawCommand::Path {
+      points: vec![Point2d { x: 400.0, y: 320.0 }, Point2d { x: 400.0, y: 300.0 }],
+      stroke_style: "white".to_string(),
+      line_width: Some(10.0),
+    }));
+    assert!(matches!(commands.last(), Some(DrawCommand::Text { text, .. }) if text == "score 0   speed 60   time 0:00"));
+  }
+
+  #[test]
+  fn scores_time_and_pills_at_the_current_speed() {
+    let mut game = SnekGame::new(0, &EngineConfig::default());
+    let mut up = KeyState::new();
+    up.press(Action::Up);
+    game.update(&up);
+    game.snek.increase_speed(60.0);
+    game.pills.push(Pill::new(PillType::ShortenSnek, 400.0, 294.0));
+    game.update(&up);
+    // a pill at double speed, plus two steps survived
+    assert_eq!(game.score().total(), 20);
+    assert!(game.events().contains(&GameEvent::ScoreChanged { score: 20 }));
+  }
+
+  #[test]
+  fn only_enemies_that_run_into_the_player_are_kills() {
+    let mut game = SnekGame::new(0, &EngineConfig::default());
+    // one enemy's head is on the player's body, the other's is on the boundary
+    game.enemy_sneks.push(AiSnek::new("red".to_string(), 40.0, Point2d { x: 396.0, y: 310.0 }, Direction::Right, 1.67));
+    game.enemy_sneks.push(AiSnek::new("red".to_string(), 40.0, Point2d { x: 302.0, y: 250.0 }, Direction::Left, 1.67));
+    let mut up = KeyState::new();
+    up.press(Action::Up);
+    game.update(&up);
+    assert!(game.enemy_sneks.iter().all(|enemy_snek| !enemy_snek.is_alive()));
+    assert_eq!(game.score().total(), 50);
+  }
+
+  #[test]
+  fn reads_gameplay_numbers_from_the_tuning() {
+    let config = EngineConfig { tuning: Some(serde_json::json!({ "player": { "speed": 90.0 } })), ..EngineConfig::default() };
+    let mut game = SnekGame::new(0, &config);
+    assert_eq!(game.snek().speed(), 90.0);
+    game.pills.push(Pill::new(PillType::IncreaseSpeed, 400.0, 300.0));
+    let mut up = KeyState::new();
+    up.press(Action::Up);
+    game.update(&up);
+    assert_eq!(game.snek().speed(), 95.0);
+  }
+
+  #[test]
+  fn a_tap_starts_the_run() {
+    let mut game = SnekGame::new(0, &EngineConfig::default());
+    let mut tap = KeyState::new();
+    tap.press(Action::Left);
+    tap.release(Action::Left);
+    game.update(&tap);
+    assert!(game.in_progress());
+    assert_eq!(game.snek().direction(), Direction::Left);
+  }
+
+  #[test]
+  fn restart_goes_back_to_waiting_for_input() {
+    let mut game = SnekGame::new(0, &EngineConfig::default());
+    game.ready = true;
+    game.end(false);
+    game.restart(7);
+    assert_eq!(game.seed(), 7);
+    assert!(!game.ready && !game.game_over);
+    assert_eq!(game.frame_number, 0);
+    assert!(game.events().is_empty());
+  }
+}
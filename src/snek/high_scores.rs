@@ -0,0 +1,114 @@
+use crate::util;
+use serde::{Deserialize, Serialize};
+
+// localStorage keys
+const STORAGE_KEY: &str = "snek.high_scores";
+const PLAYER_NAME_KEY: &str = "snek.player_name";
+
+// the table keeps this many of the best results
+pub const MAX_HIGH_SCORES: usize = 10;
+
+// name used until the player sets one
+const DEFAULT_PLAYER_NAME: &str = "player";
+
+// one finished run
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct HighScore {
+  pub score: u64,
+  // seconds survived
+  pub time: f64,
+  // replaying a run needs its seed
+  pub seed: u64,
+  // when the run ended, in milliseconds since the unix epoch
+  pub date: f64,
+  pub name: String,
+}
+
+// the best results, highest score first. kept in localStorage as a json array
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct HighScores {
+  entries: Vec<HighScore>,
+}
+
+impl HighScores {
+  pub fn entries(&self) -> &[HighScore] {
+    &self.entries
+  }
+
+  // add a result, returning its place in the table (0 is the best) if it made it in. ties
+  // go to the earlier result
+  pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+    let rank = self.entries.iter().position(|e| e.score < entry.score).unwrap_or(self.entries.len());
+    if rank >= MAX_HIGH_SCORES {
+      return None;
+    }
+    self.entries.insert(rank, entry);
+    self.entries.truncate(MAX_HIGH_SCORES);
+    Some(rank)
+  }
+
+  pub fn to_json(&self) -> String {
+    serde_json::to_string(self).unwrap()
+  }
+
+  // a table edited by hand may be out of order or too long, so it's tidied up on the way in
+  pub fn from_json(s: &str) -> Result<Self, String> {
+    let mut high_scores: Self = serde_json::from_str(s).map_err(|e| format!("invalid high scores: {e}"))?;
+    high_scores.entries.sort_by_key(|e| std::cmp::Reverse(e.score));
+    high_scores.entries.truncate(MAX_HIGH_SCORES);
+    Ok(high_scores)
+  }
+
+  // the saved table. an empty table if storage is unavailable or what's there is corrupted
+  pub fn load() -> Self {
+    util::get_item(STORAGE_KEY)
+      .and_then(|s| Self::from_json(&s).ok())
+      .unwrap_or_default()
+  }
+
+  // without storage (e.g. private browsing) the table only lasts for this session
+  pub fn save(&self) {
+    util::set_item(STORAGE_KEY, &self.to_json());
+  }
+}
+
+// the name new high scores are recorded under
+pub fn player_name() -> String {
+  util::get_item(PLAYER_NAME_KEY)
+    .unwrap_or_else(|| DEFAULT_PLAYER_NAME.to_string())
+}
+
+pub fn set_player_name(name: &str) {
+  util::set_item(PLAYER_NAME_KEY, name);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn high_score(score: u64) -> HighScore {
+    HighScore { score, time: 1.0, seed: 0, date: 0.0, name: "test".to_string() }
+  }
+
+  #[test]
+  fn keeps_the_best_scores_in_order() {
+    let mut high_scores = HighScores::default();
+    for score in 1..=MAX_HIGH_SCORES as u64 {
+      high_scores.insert(high_score(score * 10));
+    }
+    assert_eq!(high_scores.insert(high_score(5)), None);
+    assert_eq!(high_scores.insert(high_score(55)), Some(5));
+    assert_eq!(high_scores.entries().len(), MAX_HIGH_SCORES);
+    assert_eq!(high_scores.entries()[0].score, 100);
+    assert_eq!(high_scores.entries()[MAX_HIGH_SCORES - 1].score, 20);
+  }
+
+  #[test]
+  fn tidies_up_loaded_tables() {
+    let json = r#"[{"score":1,"time":0,"seed":0,"date":0,"name":"a"},{"score":9,"time":0,"seed":0,"date":0,"name":"b"}]"#;
+    let high_scores = HighScores::from_json(json).unwrap();
+    assert_eq!(high_scores.entries()[0].name, "b");
+    assert!(HighScores::from_json("[{\"score\":").is_err());
+  }
+}
@@ -1,6 +1,21 @@
+mod app;
+mod autopilot;
 mod collision;
 mod entity;
 mod game;
+mod high_scores;
 mod pill;
+mod placement;
+mod save;
+mod scenes;
+mod score;
+mod spawn;
+mod tuning;
 
-pub use game::{SnekGame};
+pub use app::SnekApp;
+pub use autopilot::autopilot;
+pub use game::SnekGame;
+pub use high_scores::{HighScore, HighScores, player_name, set_player_name};
+pub use pill::PillType;
+pub use spawn::{SpawnRule, SpawnTable};
+pub use tuning::{BoundaryTuning, EnemyTuning, PillTuning, PlacementTuning, PlayerTuning, Tuning};
@@ -1,8 +1,9 @@
 use crate::engine::{Point2d,Renderer};
-use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
 
 const RADIUS: f64 = 5.0;
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum PillType {
   ExpandBoundary,
   ShortenSnek,
@@ -11,16 +12,33 @@ pub enum PillType {
 }
 
 impl PillType {
-  fn to_js_value(&self) -> JsValue {
+  pub const ALL: [PillType; 4] = [
+    PillType::ExpandBoundary,
+    PillType::ShortenSnek,
+    PillType::SpawnEnemySnek,
+    PillType::IncreaseSpeed,
+  ];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      PillType::ExpandBoundary => "expand boundary",
+      PillType::ShortenSnek => "shorten snek",
+      PillType::SpawnEnemySnek => "spawn enemy snek",
+      PillType::IncreaseSpeed => "increase speed",
+    }
+  }
+
+  fn color(&self) -> &'static str {
     match self {
-      PillType::ExpandBoundary => JsValue::from("white"),
-      PillType::ShortenSnek => JsValue::from("blue"),
-      PillType::SpawnEnemySnek => JsValue::from("red"),
-      PillType::IncreaseSpeed => JsValue::from("green"),
+      PillType::ExpandBoundary => "white",
+      PillType::ShortenSnek => "blue",
+      PillType::SpawnEnemySnek => "red",
+      PillType::IncreaseSpeed => "green",
     }
   }
 }
 
+#[derive(Deserialize, Serialize)]
 pub struct Pill {
   pub pill_type: PillType,
   pub position: Point2d,
@@ -31,9 +49,9 @@ impl Pill {
     Self { pill_type, position: Point2d { x, y } }
   }
 
-  pub fn draw(&self, renderer: &Renderer) {
+  pub fn draw(&self, renderer: &dyn Renderer) {
     renderer.circle(&self.position, RADIUS,
-                    Some(&self.pill_type.to_js_value()),
+                    Some(self.pill_type.color()),
                     None,
                     None);
   }
@@ -0,0 +1,167 @@
+use crate::engine::{Point2d, Rect};
+use crate::snek::entity::{AiSnek, Boundary, Direction, Snek, START_LENGTH};
+use crate::snek::pill::Pill;
+use crate::snek::tuning::PlacementTuning;
+use rand::Rng;
+
+// random points tried before giving up on a spawn
+const ATTEMPTS: usize = 50;
+// half the width of a snek
+const HALF_WIDTH: f64 = 5.0;
+
+// everything on the field that a new pill or enemy snek must not appear on top of
+pub struct Field<'a> {
+  boundary: &'a Boundary,
+  sneks: Vec<&'a Snek>,
+  pills: &'a [Pill],
+  head: Point2d,
+}
+
+impl<'a> Field<'a> {
+  pub fn new(boundary: &'a Boundary, player: &'a Snek, enemies: &'a [AiSnek], pills: &'a [Pill]) -> Self {
+    let mut sneks = vec![player];
+    sneks.extend(enemies.iter().map(AiSnek::get));
+    let head = player.path()[player.path().len() - 1].clone();
+    Self { boundary, sneks, pills, head }
+  }
+
+  // a point clear of the player's head, the sneks and the pills. none if there doesn't seem
+  // to be one, in which case nothing should spawn
+  pub fn pill_position(&self, rng: &mut impl Rng, tuning: &PlacementTuning) -> Option<Point2d> {
+    (0..ATTEMPTS)
+      .map(|_| self.boundary.random_point(rng))
+      .find(|p| self.is_clear(p, tuning))
+  }
+
+  // where a new enemy snek's head goes and the direction it sets off in: the one with the
+  // most room ahead of it, among those with room for its body behind it
+  pub fn enemy_start(&self, rng: &mut impl Rng, tuning: &PlacementTuning) -> Option<(Point2d, Direction)> {
+    (0..ATTEMPTS)
+      .map(|_| self.boundary.random_point(rng))
+      .filter(|p| self.is_clear(p, tuning))
+      .find_map(|p| self.open_direction(&p, tuning).map(|direction| (p, direction)))
+  }
+
+  fn is_clear(&self, p: &Point2d, tuning: &PlacementTuning) -> bool {
+    distance(p, &self.head) >= tuning.safe_radius
+      && self.sneks.iter().all(|snek| distance_to_path(p, snek.path()) >= tuning.clearance + HALF_WIDTH)
+      && self.pills.iter().all(|pill| distance(p, &pill.position) >= tuning.clearance)
+  }
+
+  fn open_direction(&self, p: &Point2d, tuning: &PlacementTuning) -> Option<Direction> {
+    [Direction::Up, Direction::Down, Direction::Left, Direction::Right].into_iter()
+      .filter(|direction| self.room(p, direction.opposite()) >= START_LENGTH + tuning.clearance)
+      .max_by(|a, b| self.room(p, *a).total_cmp(&self.room(p, *b)))
+  }
+
+  // how far a snek's head could go from p in direction before touching the boundary or
+  // another snek
+  fn room(&self, p: &Point2d, direction: Direction) -> f64 {
+    let Rect { x, y, width, height } = self.boundary.rect();
+    let inside = Rect::new(x + 2.0 * HALF_WIDTH, y + 2.0 * HALF_WIDTH, width - 4.0 * HALF_WIDTH, height - 4.0 * HALF_WIDTH);
+    let to_boundary = match direction {
+      Direction::Up    => p.y - inside.y,
+      Direction::Down  => inside.y + inside.height - p.y,
+      Direction::Left  => p.x - inside.x,
+      Direction::Right => inside.x + inside.width - p.x,
+    };
+    self.sneks.iter()
+      .flat_map(|snek| snek.path().windows(2))
+      .filter_map(|segment| ray_to_segment(p, direction, &segment[0], &segment[1]))
+      .fold(to_boundary, f64::min)
+  }
+}
+
+fn distance(a: &Point2d, b: &Point2d) -> f64 {
+  (a.x - b.x).hypot(a.y - b.y)
+}
+
+// the distance from p to the nearest point of path
+pub fn distance_to_path(p: &Point2d, path: &[Point2d]) -> f64 {
+  path.windows(2)
+    .map(|segment| {
+      let (a, b) = (&segment[0], &segment[1]);
+      // path segments are horizontal or vertical, so the nearest point is a clamp
+      let x = p.x.clamp(a.x.min(b.x), a.x.max(b.x));
+      let y = p.y.clamp(a.y.min(b.y), a.y.max(b.y));
+      distance(p, &Point2d { x, y })
+    })
+    .fold(f64::INFINITY, f64::min)
+}
+
+// how far a snek's head can go from p in direction before it touches the segment a-b of
+// another snek, if it ever does. both have a width, so the segment is widened by both halves
+fn ray_to_segment(p: &Point2d, direction: Direction, a: &Point2d, b: &Point2d) -> Option<f64> {
+  let r = 2.0 * HALF_WIDTH;
+  let (left, right) = (a.x.min(b.x) - r, a.x.max(b.x) + r);
+  let (top, bottom) = (a.y.min(b.y) - r, a.y.max(b.y) + r);
+  let across_x = left <= p.x && p.x <= right;
+  let across_y = top <= p.y && p.y <= bottom;
+  match direction {
+    Direction::Up    if across_x && top <= p.y    => Some((p.y - bottom).max(0.0)),
+    Direction::Down  if across_x && bottom >= p.y => Some((top - p.y).max(0.0)),
+    Direction::Left  if across_y && left <= p.x   => Some((p.x - right).max(0.0)),
+    Direction::Right if across_y && right >= p.x  => Some((left - p.x).max(0.0)),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::SeedableRng;
+  use rand_pcg::Pcg32;
+
+  fn tuning() -> PlacementTuning {
+    PlacementTuning { safe_radius: 80.0, clearance: 15.0 }
+  }
+
+  fn arena() -> Rect {
+    Rect::new(0.0, 0.0, 800.0, 600.0)
+  }
+
+  #[test]
+  fn pills_keep_clear_of_the_head_sneks_and_other_pills() {
+    let boundary = Boundary::new(&arena());
+    let player = Snek::new("white".to_string(), 60.0, Point2d { x: 400.0, y: 300.0 }, Direction::Up);
+    let enemies = [AiSnek::new("red".to_string(), 40.0, Point2d { x: 330.0, y: 250.0 }, Direction::Right, 1.67)];
+    let pills = [Pill::new(crate::snek::pill::PillType::ShortenSnek, 460.0, 240.0)];
+    let field = Field::new(&boundary, &player, &enemies, &pills);
+    let mut rng = Pcg32::seed_from_u64(0);
+    for _ in 0..200 {
+      let p = field.pill_position(&mut rng, &tuning()).unwrap();
+      assert!(distance(&p, &Point2d { x: 400.0, y: 300.0 }) >= 80.0);
+      assert!(distance_to_path(&p, enemies[0].get().path()) >= 20.0);
+      assert!(distance(&p, &pills[0].position) >= 15.0);
+    }
+  }
+
+  #[test]
+  fn gives_up_when_there_is_no_room() {
+    let boundary = Boundary::new(&arena());
+    let player = Snek::new("white".to_string(), 60.0, Point2d { x: 400.0, y: 300.0 }, Direction::Up);
+    let field = Field::new(&boundary, &player, &[], &[]);
+    let mut rng = Pcg32::seed_from_u64(0);
+    assert_eq!(field.pill_position(&mut rng, &PlacementTuning { safe_radius: 500.0, clearance: 15.0 }), None);
+  }
+
+  #[test]
+  fn enemies_set_off_into_open_space() {
+    let boundary = Boundary::new(&arena());
+    // the boundary spans 300..500 by 200..400, and the player is heading for its right side
+    let player = Snek::new("white".to_string(), 60.0, Point2d { x: 480.0, y: 240.0 }, Direction::Right);
+    let field = Field::new(&boundary, &player, &[], &[]);
+    assert_eq!(field.room(&Point2d { x: 320.0, y: 245.0 }, Direction::Right), 130.0);
+    assert_eq!(field.room(&Point2d { x: 400.0, y: 300.0 }, Direction::Up), 90.0);
+    // next to the left wall there's no room for the body of a snek heading right, and
+    // there's more room downwards than towards the player
+    assert_eq!(field.room(&Point2d { x: 320.0, y: 245.0 }, Direction::Left), 10.0);
+    assert_eq!(field.open_direction(&Point2d { x: 320.0, y: 245.0 }, &tuning()), Some(Direction::Down));
+
+    let mut rng = Pcg32::seed_from_u64(0);
+    for _ in 0..100 {
+      let (p, direction) = field.enemy_start(&mut rng, &tuning()).unwrap();
+      assert!(field.room(&p, direction.opposite()) >= 35.0);
+    }
+  }
+}
@@ -0,0 +1,112 @@
+use crate::engine::EngineConfig;
+use crate::snek::game::SnekGame;
+use crate::util;
+use serde::{Deserialize, Serialize};
+
+// localStorage key of the run saved when the page was last hidden
+const STORAGE_KEY: &str = "snek.saved_run";
+
+// bumped whenever SnekGame's serialized form changes. saves from other versions are
+// ignored rather than restored wrongly
+const SAVE_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Save<'a> {
+  version: u32,
+  game: &'a SnekGame,
+}
+
+#[derive(Deserialize)]
+struct Version {
+  version: u32,
+}
+
+#[derive(Deserialize)]
+struct Saved {
+  game: SnekGame,
+}
+
+pub fn to_json(game: &SnekGame) -> String {
+  serde_json::to_string(&Save { version: SAVE_VERSION, game }).unwrap()
+}
+
+// the saved run, if it was made with config. a run from a canvas of another size or tick
+// rate, or with other tuning, would play wrongly in this one
+pub fn from_json(s: &str, config: &EngineConfig) -> Result<SnekGame, String> {
+  let Version { version } = serde_json::from_str(s).map_err(|e| format!("invalid save: {e}"))?;
+  if version != SAVE_VERSION {
+    return Err(format!("unsupported save version {version}"));
+  }
+  let Saved { game } = serde_json::from_str(s).map_err(|e| format!("invalid save: {e}"))?;
+  if !same_settings(game.config(), config) {
+    return Err("saved with different settings".to_string());
+  }
+  Ok(game)
+}
+
+// the settings a run plays differently under. the rest, like the canvas id or the colors,
+// can change between sessions
+fn same_settings(a: &EngineConfig, b: &EngineConfig) -> bool {
+  (a.width, a.height, a.tick_rate, &a.tuning) == (b.width, b.height, b.tick_rate, &b.tuning)
+}
+
+// keep a run in progress so it can be continued in a later session
+pub fn save(game: &SnekGame) {
+  util::set_item(STORAGE_KEY, &to_json(game));
+}
+
+// the saved run, if there is one that can be restored in a game with config
+pub fn load(config: &EngineConfig) -> Option<SnekGame> {
+  util::get_item(STORAGE_KEY)
+    .and_then(|s| from_json(&s, config).ok())
+}
+
+// forget the saved run, once it's finished or been replaced by a new one
+pub fn clear() {
+  util::remove_item(STORAGE_KEY);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::{Action, EngineConfig, KeyState, RecordingRenderer};
+
+  #[test]
+  fn a_restored_run_continues_exactly() {
+    let mut game = SnekGame::new(3, &EngineConfig::default());
+    let mut left = KeyState::new();
+    left.press(Action::Left);
+    for _ in 0..20 {
+      game.update(&left);
+    }
+    let mut restored = from_json(&to_json(&game), &EngineConfig::default()).unwrap();
+    for _ in 0..20 {
+      game.update(&KeyState::new());
+      restored.update(&KeyState::new());
+    }
+
+    // the serialized state includes the random number generator's
+    assert_eq!(to_json(&restored), to_json(&game));
+    let (a, b) = (RecordingRenderer::new(), RecordingRenderer::new());
+    game.draw(&a, 0.5);
+    restored.draw(&b, 0.5);
+    assert_eq!(a.take(), b.take());
+  }
+
+  #[test]
+  fn rejects_other_versions() {
+    let json = to_json(&SnekGame::new(0, &EngineConfig::default())).replacen("\"version\":1", "\"version\":0", 1);
+    assert_eq!(from_json(&json, &EngineConfig::default()).err(), Some("unsupported save version 0".to_string()));
+  }
+
+  #[test]
+  fn rejects_runs_saved_with_other_settings() {
+    let json = to_json(&SnekGame::new(0, &EngineConfig::default()));
+    let config = EngineConfig { width: 640, ..EngineConfig::default() };
+    assert_eq!(from_json(&json, &config).err(), Some("saved with different settings".to_string()));
+    let config = EngineConfig { tuning: Some(serde_json::json!({ "player": { "speed": 80.0 } })), ..EngineConfig::default() };
+    assert!(from_json(&json, &config).is_err());
+    let config = EngineConfig { canvas_id: "game".to_string(), background_color: "navy".to_string(), seed: Some(7), ..EngineConfig::default() };
+    assert!(from_json(&json, &config).is_ok());
+  }
+}
@@ -0,0 +1,168 @@
+use crate::util;
+use crate::engine::{Action, KeyState, Renderer, Scene, SceneChange};
+use crate::snek::game::SnekGame;
+use crate::snek::high_scores::{HighScore, HighScores, player_name};
+use crate::snek::save;
+use crate::snek::score::format_time;
+
+// center of the canvas, which the text of every screen is laid out around
+fn center(game: &SnekGame) -> (f64, f64) {
+  let bounds = game.bounds();
+  (bounds.width / 2.0, bounds.height / 2.0)
+}
+
+// darken whatever is drawn underneath
+fn dim(game: &SnekGame, renderer: &dyn Renderer) {
+  renderer.rect(
+    game.bounds(),
+    Some("rgba(0, 0, 0, 0.6)"),
+    None,
+    None);
+}
+
+// shown when the page loads, over the field the run will start on. if a run was saved
+// when the page was last hidden, starting continues it. the restart action starts over
+pub struct TitleScene {
+  saved: Option<SnekGame>,
+  // the run is a replay being played back
+  playback: bool,
+}
+
+impl TitleScene {
+  pub fn new(saved: Option<SnekGame>, playback: bool) -> Self {
+    Self { saved, playback }
+  }
+}
+
+impl Scene<SnekGame> for TitleScene {
+  fn update(&mut self, game: &mut SnekGame, key_state: &KeyState) -> SceneChange<SnekGame> {
+    // a direction starts the game too, since touch screens can't press confirm
+    let start = [Action::Confirm, Action::Up, Action::Down, Action::Left, Action::Right];
+    if start.iter().any(|action| key_state.just_pressed(*action)) {
+      if let Some(saved) = self.saved.take() {
+        game.restore(saved);
+      }
+      SceneChange::Replace(Box::new(PlayScene::new(self.playback)))
+    } else {
+      SceneChange::None
+    }
+  }
+
+  fn draw(&self, game: &SnekGame, renderer: &dyn Renderer, alpha: f64) {
+    game.draw(renderer, alpha);
+    dim(game, renderer);
+    let (x, y) = center(game);
+    renderer.text("snek", "white", 40, x - 55.0, y - 20.0);
+    if self.saved.is_some() {
+      renderer.text("press enter to continue", "white", 14, x - 105.0, y + 30.0);
+      renderer.text("press R to start over", "white", 14, x - 95.0, y + 55.0);
+    } else {
+      renderer.text("press enter to start", "white", 14, x - 90.0, y + 30.0);
+    }
+  }
+}
+
+// the game itself, until the run is over
+pub struct PlayScene {
+  // the run is a replay being played back
+  playback: bool,
+}
+
+impl PlayScene {
+  pub fn new(playback: bool) -> Self {
+    Self { playback }
+  }
+}
+
+impl Scene<SnekGame> for PlayScene {
+  fn update(&mut self, game: &mut SnekGame, key_state: &KeyState) -> SceneChange<SnekGame> {
+    game.update(key_state);
+    if game.is_over() {
+      let results = if self.playback { ResultsScene::played_back() } else { ResultsScene::record(game) };
+      SceneChange::Replace(Box::new(results))
+    } else {
+      SceneChange::None
+    }
+  }
+
+  fn draw(&self, game: &SnekGame, renderer: &dyn Renderer, alpha: f64) {
+    game.draw(renderer, alpha);
+  }
+}
+
+// drawn over the game while the engine is paused
+pub struct PauseScene;
+
+impl Scene<SnekGame> for PauseScene {
+  fn update(&mut self, _game: &mut SnekGame, _key_state: &KeyState) -> SceneChange<SnekGame> {
+    SceneChange::None
+  }
+
+  fn draw(&self, game: &SnekGame, renderer: &dyn Renderer, _alpha: f64) {
+    dim(game, renderer);
+    let (x, y) = center(game);
+    renderer.text("PAUSED", "white", 30, x - 85.0, y + 15.0);
+  }
+
+  fn is_overlay(&self) -> bool {
+    true
+  }
+}
+
+// the outcome of a finished run and the high score table. the restart action starts the
+// next one
+pub struct ResultsScene {
+  high_scores: HighScores,
+  // where the finished run placed in the table, if it made it in
+  rank: Option<usize>,
+}
+
+impl ResultsScene {
+  // add the finished run to the saved high score table. a finished run can't be continued,
+  // so any save of it is dropped
+  fn record(game: &SnekGame) -> Self {
+    save::clear();
+    let mut high_scores = HighScores::load();
+    let rank = high_scores.insert(HighScore {
+      score: game.score().total(),
+      time: game.score().elapsed(),
+      seed: game.seed(),
+      date: util::timestamp(),
+      name: player_name(),
+    });
+    high_scores.save();
+    Self { high_scores, rank }
+  }
+
+  // a replay that was played back isn't the viewer's run, so it's shown next to their
+  // table without being added to it or dropping their save
+  fn played_back() -> Self {
+    Self { high_scores: HighScores::load(), rank: None }
+  }
+}
+
+impl Scene<SnekGame> for ResultsScene {
+  fn update(&mut self, _game: &mut SnekGame, _key_state: &KeyState) -> SceneChange<SnekGame> {
+    SceneChange::None
+  }
+
+  fn draw(&self, game: &SnekGame, renderer: &dyn Renderer, _alpha: f64) {
+    game.draw_background(renderer);
+    let (x, y) = center(game);
+    renderer.text("GAME OVER", "red", 30, x - 120.0, y - 140.0);
+    if game.won() {
+      renderer.text("you have won", "red", 20, x - 80.0, y - 100.0);
+    }
+    let score = game.score();
+    let result = format!("score {} in {}", score.total(), format_time(score.elapsed()));
+    renderer.text(&result, "white", 14, x - 80.0, y - 60.0);
+    renderer.text("press R to play again", "white", 14, x - 95.0, y - 30.0);
+
+    renderer.text("high scores", "white", 14, x - 55.0, y + 20.0);
+    for (i, entry) in self.high_scores.entries().iter().enumerate() {
+      let color = if self.rank == Some(i) { "yellow" } else { "white" };
+      let line = format!("{:>2}. {:<12} {:>6}  {}", i + 1, entry.name, entry.score, format_time(entry.time));
+      renderer.text(&line, color, 10, x - 110.0, y + 45.0 + 18.0 * i as f64);
+    }
+  }
+}
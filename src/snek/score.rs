@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+// points for what a run achieves, before the speed multiplier
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Award {
+  Pill,
+  EnemyKilled,
+  Exit,
+}
+
+impl Award {
+  fn points(self) -> f64 {
+    match self {
+      Award::Pill => 10.0,
+      Award::EnemyKilled => 50.0,
+      Award::Exit => 500.0,
+    }
+  }
+}
+
+// points for every second survived, before the speed multiplier
+const POINTS_PER_SECOND: f64 = 1.0;
+
+// the score of a run. everything is worth more the faster the snek is going, relative to
+// the speed it started at
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Score {
+  points: f64,
+  // seconds survived
+  elapsed: f64,
+}
+
+impl Score {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // whole points scored so far
+  pub fn total(&self) -> u64 {
+    self.points as u64
+  }
+
+  pub fn elapsed(&self) -> f64 {
+    self.elapsed
+  }
+
+  // survive dt more seconds
+  pub fn survive(&mut self, dt: f64, multiplier: f64) {
+    self.elapsed += dt;
+    self.points += POINTS_PER_SECOND * dt * multiplier;
+  }
+
+  pub fn award(&mut self, award: Award, multiplier: f64) {
+    self.points += award.points() * multiplier;
+  }
+}
+
+// how much a snek moving at speed scores, relative to one moving at its starting speed
+pub fn speed_multiplier(speed: f64, starting_speed: f64) -> f64 {
+  speed / starting_speed
+}
+
+// elapsed seconds as m:ss
+pub fn format_time(seconds: f64) -> String {
+  let seconds = seconds as u64;
+  format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn faster_sneks_score_more() {
+    let mut score = Score::new();
+    score.survive(0.5, 1.0);
+    score.survive(0.5, 1.0);
+    score.award(Award::Pill, speed_multiplier(60.0, 60.0));
+    score.award(Award::EnemyKilled, speed_multiplier(90.0, 60.0));
+    assert_eq!(score.total(), 1 + 10 + 75);
+    assert_eq!(format_time(score.elapsed() + 60.0), "1:01");
+  }
+}
@@ -0,0 +1,169 @@
+use crate::snek::pill::PillType;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// how one type of pill spawns. weights are relative to the other types, and can drift as a
+// run goes on so the mix of pills changes
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct SpawnRule {
+  // weight at the start of a run
+  pub weight: f64,
+  // added to the weight for each minute of the run. can be negative
+  #[serde(default)]
+  pub weight_per_minute: f64,
+  // added to the weight for every 100 pixels the boundary has grown wider. can be negative
+  #[serde(default, rename = "weightPer100px")]
+  pub weight_per_100px: f64,
+  // while there are fewer than this many on the field, one spawns every step
+  #[serde(default)]
+  pub min: u32,
+  // never more than this many on the field at once
+  #[serde(default)]
+  pub max: Option<u32>,
+  // seconds after one spawns before the next can
+  #[serde(default)]
+  pub cooldown: f64,
+}
+
+// picks which pill spawns next. serializable so a saved run keeps its cooldowns
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SpawnTable {
+  rules: BTreeMap<PillType, SpawnRule>,
+  // seconds into the run each type last spawned
+  last_spawned: BTreeMap<PillType, f64>,
+}
+
+impl SpawnTable {
+  pub fn new(rules: BTreeMap<PillType, SpawnRule>) -> Self {
+    Self { rules, last_spawned: BTreeMap::new() }
+  }
+
+  pub fn validate(rules: &BTreeMap<PillType, SpawnRule>) -> Result<(), String> {
+    for (pill_type, rule) in rules {
+      if rule.max.is_some_and(|max| rule.min > max) {
+        return Err(format!("{} has a min above its max", pill_type.name()));
+      }
+      if ![rule.weight, rule.weight_per_minute, rule.weight_per_100px].iter().all(|w| w.is_finite()) {
+        return Err(format!("{} has a weight that isn't a finite number", pill_type.name()));
+      }
+    }
+    // choose picks a number below the total weight, which has to be finite
+    if !rules.values().map(|rule| rule.weight).sum::<f64>().is_finite() {
+      return Err("the weights add up to more than can be picked from".to_string());
+    }
+    Ok(())
+  }
+
+  // the weight of pill_type elapsed seconds into a run, with the boundary grown by growth
+  // pixels. never below 0
+  pub fn weight(&self, pill_type: PillType, elapsed: f64, growth: f64) -> f64 {
+    self.rules.get(&pill_type).map_or(0.0, |rule| {
+      let weight = rule.weight + rule.weight_per_minute * elapsed / 60.0 + rule.weight_per_100px * growth / 100.0;
+      weight.max(0.0)
+    })
+  }
+
+  // under its max and off cooldown. on_field is how many of each type are on the field
+  fn can_spawn(&self, pill_type: PillType, elapsed: f64, on_field: &BTreeMap<PillType, u32>) -> bool {
+    let Some(rule) = self.rules.get(&pill_type) else { return false; };
+    let count = on_field.get(&pill_type).copied().unwrap_or(0);
+    let cooled_down = self.last_spawned.get(&pill_type).is_none_or(|last| elapsed - last >= rule.cooldown);
+    rule.max.is_none_or(|max| count < max) && cooled_down
+  }
+
+  // a type with fewer on the field than its min that can spawn now
+  pub fn below_min(&self, elapsed: f64, on_field: &BTreeMap<PillType, u32>) -> Option<PillType> {
+    self.rules.iter()
+      .find(|(pill_type, rule)| {
+        on_field.get(pill_type).copied().unwrap_or(0) < rule.min && self.can_spawn(**pill_type, elapsed, on_field)
+      })
+      .map(|(pill_type, _)| *pill_type)
+  }
+
+  // a type picked at random in proportion to the weights of those that can spawn now.
+  // none if nothing can
+  pub fn choose(&self, rng: &mut impl Rng, elapsed: f64, growth: f64, on_field: &BTreeMap<PillType, u32>) -> Option<PillType> {
+    let weights: Vec<(PillType, f64)> = self.rules.keys()
+      .filter(|pill_type| self.can_spawn(**pill_type, elapsed, on_field))
+      .map(|pill_type| (*pill_type, self.weight(*pill_type, elapsed, growth)))
+      .filter(|(_, weight)| *weight > 0.0)
+      .collect();
+    let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+    if total <= 0.0 {
+      return None;
+    }
+    let mut n = rng.gen_range(0.0..total);
+    for (pill_type, weight) in &weights {
+      if n < *weight {
+        return Some(*pill_type);
+      }
+      n -= weight;
+    }
+    // only reachable through rounding
+    weights.last().map(|(pill_type, _)| *pill_type)
+  }
+
+  // start the cooldown of pill_type
+  pub fn spawned(&mut self, pill_type: PillType, elapsed: f64) {
+    self.last_spawned.insert(pill_type, elapsed);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::SeedableRng;
+  use rand_pcg::Pcg32;
+
+  fn rule(weight: f64) -> SpawnRule {
+    SpawnRule { weight, weight_per_minute: 0.0, weight_per_100px: 0.0, min: 0, max: None, cooldown: 0.0 }
+  }
+
+  #[test]
+  fn picks_in_proportion_to_the_weights() {
+    let table = SpawnTable::new(BTreeMap::from([
+      (PillType::ExpandBoundary, rule(3.0)),
+      (PillType::ShortenSnek, rule(1.0)),
+    ]));
+    let mut rng = Pcg32::seed_from_u64(0);
+    let expands = (0..4000)
+      .filter(|_| table.choose(&mut rng, 0.0, 0.0, &BTreeMap::new()) == Some(PillType::ExpandBoundary))
+      .count();
+    assert!((2850..3150).contains(&expands), "{expands}");
+  }
+
+  #[test]
+  fn weights_shift_with_time_and_boundary_size() {
+    let table = SpawnTable::new(BTreeMap::from([
+      (PillType::SpawnEnemySnek, SpawnRule { weight_per_minute: 2.0, weight_per_100px: -1.0, ..rule(1.0) }),
+    ]));
+    assert_eq!(table.weight(PillType::SpawnEnemySnek, 0.0, 0.0), 1.0);
+    assert_eq!(table.weight(PillType::SpawnEnemySnek, 90.0, 100.0), 3.0);
+    assert_eq!(table.weight(PillType::SpawnEnemySnek, 0.0, 300.0), 0.0);
+    assert_eq!(table.weight(PillType::IncreaseSpeed, 90.0, 100.0), 0.0);
+  }
+
+  #[test]
+  fn respects_counts_and_cooldowns() {
+    let mut table = SpawnTable::new(BTreeMap::from([
+      (PillType::ExpandBoundary, SpawnRule { min: 1, max: Some(2), ..rule(1.0) }),
+      (PillType::IncreaseSpeed, SpawnRule { cooldown: 10.0, ..rule(1.0) }),
+    ]));
+    let mut rng = Pcg32::seed_from_u64(0);
+    assert_eq!(table.below_min(0.0, &BTreeMap::new()), Some(PillType::ExpandBoundary));
+
+    let full = BTreeMap::from([(PillType::ExpandBoundary, 2)]);
+    assert_eq!(table.below_min(0.0, &full), None);
+    table.spawned(PillType::IncreaseSpeed, 0.0);
+    assert_eq!(table.choose(&mut rng, 5.0, 0.0, &full), None);
+    assert_eq!(table.choose(&mut rng, 10.0, 0.0, &full), Some(PillType::IncreaseSpeed));
+  }
+
+  #[test]
+  fn rejects_a_min_above_the_max() {
+    let rules = BTreeMap::from([(PillType::ShortenSnek, SpawnRule { min: 3, max: Some(1), ..rule(1.0) })]);
+    assert!(SpawnTable::validate(&rules).is_err());
+  }
+}
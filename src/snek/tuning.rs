@@ -0,0 +1,162 @@
+use crate::engine::EngineConfig;
+use crate::snek::pill::PillType;
+use crate::snek::spawn::{SpawnRule, SpawnTable};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+// the tuning the game ships with. overrides only need the fields they change
+const DEFAULT_TUNING: &str = include_str!("tuning.json");
+
+// every gameplay number, so the game can be balanced without touching the code. read from
+// the `tuning` field of the engine config, on top of the defaults in tuning.json
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Tuning {
+  pub player: PlayerTuning,
+  pub enemy: EnemyTuning,
+  pub boundary: BoundaryTuning,
+  pub pills: PillTuning,
+  pub placement: PlacementTuning,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct PlayerTuning {
+  // pixels per second at the start of a run
+  pub speed: f64,
+  // added to the speed by each increase speed pill
+  pub speed_boost: f64,
+  // fraction of its length a shorten snek pill takes off
+  pub shorten_by: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct EnemyTuning {
+  // pixels per second
+  pub speed: f64,
+  // seconds between turns
+  pub turn_every: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct BoundaryTuning {
+  // pixels each side moves out by for an expand boundary pill
+  pub expand_by: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct PillTuning {
+  // the rate pills spawn at, per second, grows by this much for every second since the
+  // last one spawned
+  pub spawn_rate_growth: f64,
+  // which type of pill each spawn is. a type left out never spawns
+  pub spawn_table: BTreeMap<PillType, SpawnRule>,
+}
+
+// where new pills and enemy sneks may appear
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct PlacementTuning {
+  // nothing appears closer than this many pixels to the player's head
+  pub safe_radius: f64,
+  // pixels kept between anything new and the sneks and pills already on the field
+  pub clearance: f64,
+}
+
+impl Default for Tuning {
+  fn default() -> Self {
+    serde_json::from_str(DEFAULT_TUNING).expect("tuning.json is valid")
+  }
+}
+
+impl Tuning {
+  // the defaults with the fields in overrides replaced. objects are merged field by field,
+  // so `{"player": {"speed": 80}}` leaves the rest of the player tuning alone
+  pub fn with_overrides(overrides: &Value) -> Result<Self, String> {
+    let mut tuning: Value = serde_json::from_str(DEFAULT_TUNING).expect("tuning.json is valid");
+    merge(&mut tuning, overrides);
+    let tuning: Self = serde_json::from_value(tuning).map_err(|e| format!("invalid tuning: {e}"))?;
+    SpawnTable::validate(&tuning.pills.spawn_table).map_err(|e| format!("invalid tuning: {e}"))?;
+    tuning.validate().map_err(|e| format!("invalid tuning: {e}"))?;
+    Ok(tuning)
+  }
+
+  // numbers that deserialize fine but that the game can't be played with
+  fn validate(&self) -> Result<(), String> {
+    let positive = |value: f64| value.is_finite() && value > 0.0;
+    let non_negative = |value: f64| value.is_finite() && value >= 0.0;
+    let checks = [
+      ("player.speed", positive(self.player.speed), "above 0"),
+      ("player.shortenBy", (0.0..1.0).contains(&self.player.shorten_by), "at least 0 and below 1"),
+      ("enemy.speed", positive(self.enemy.speed), "above 0"),
+      ("enemy.turnEvery", positive(self.enemy.turn_every), "above 0"),
+      ("boundary.expandBy", non_negative(self.boundary.expand_by), "at least 0"),
+      ("placement.safeRadius", non_negative(self.placement.safe_radius), "at least 0"),
+      ("placement.clearance", non_negative(self.placement.clearance), "at least 0"),
+    ];
+    match checks.iter().find(|(_, ok, _)| !ok) {
+      Some((field, _, requirement)) => Err(format!("{field} must be {requirement}")),
+      None => Ok(()),
+    }
+  }
+
+  // the tuning a game created with config should use
+  pub fn from_config(config: &EngineConfig) -> Result<Self, String> {
+    match &config.tuning {
+      Some(overrides) => Self::with_overrides(overrides),
+      None => Ok(Self::default()),
+    }
+  }
+}
+
+fn merge(base: &mut Value, overrides: &Value) {
+  match (base, overrides) {
+    (Value::Object(base), Value::Object(overrides)) => {
+      for (key, value) in overrides {
+        match base.get_mut(key) {
+          Some(existing) => merge(existing, value),
+          None => { base.insert(key.clone(), value.clone()); },
+        }
+      }
+    },
+    (base, overrides) => { *base = overrides.clone(); },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn overrides_replace_only_the_fields_they_name() {
+    let tuning = Tuning::with_overrides(&json!({ "player": { "speed": 80.0 }, "pills": { "spawnTable": { "ShortenSnek": { "max": 2 } } } })).unwrap();
+    assert_eq!(tuning.player.speed, 80.0);
+    assert_eq!(tuning.player.speed_boost, Tuning::default().player.speed_boost);
+    assert_eq!(tuning.pills.spawn_table[&PillType::ExpandBoundary].weight, 3.0);
+    assert_eq!(tuning.pills.spawn_table[&PillType::ShortenSnek].weight, 1.0);
+    assert_eq!(tuning.pills.spawn_table[&PillType::ShortenSnek].max, Some(2));
+  }
+
+  #[test]
+  fn rejects_misspelled_and_mistyped_fields() {
+    assert!(Tuning::with_overrides(&json!({ "player": { "sped": 80.0 } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "enemy": { "turnEvery": "often" } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "pills": { "spawnTable": { "Bomb": { "weight": 1.0 } } } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "pills": { "spawnTable": { "ShortenSnek": { "min": 2, "max": 1 } } } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "player": { "shortenBy": 1.0 } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "player": { "shortenBy": -0.1 } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "player": { "speed": 0.0 } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "enemy": { "speed": -40.0 } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "enemy": { "turnEvery": 0.0 } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "boundary": { "expandBy": -5.0 } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "placement": { "safeRadius": -1.0 } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "placement": { "clearance": -1.0 } })).is_err());
+    assert!(Tuning::with_overrides(&json!({ "pills": { "spawnTable": { "ShortenSnek": { "weight": f64::MAX }, "IncreaseSpeed": { "weight": f64::MAX } } } })).is_err());
+    assert_eq!(Tuning::with_overrides(&json!({ "player": { "shortenBy": 0.0 }, "boundary": { "expandBy": 0.0 } })).map(|_| ()), Ok(()));
+  }
+}
@@ -1,36 +1,60 @@
-use wasm_bindgen::JsCast;
-use wasm_bindgen::prelude::*;
-use web_sys;
+// services that work differently in the browser and natively (in tests and the cli). the
+// browser versions live in the engine's browser backend
 
 // make printing a console log more convenient
+#[allow(unused_macros)]
 macro_rules! log {
   ( $( $t:tt )* ) => {
-    web_sys::console::log_1(&format!( $( $t )* ).into());
+    $crate::util::log(&format!( $( $t )* ));
   }
 }
 
-pub fn get_canvas() -> web_sys::HtmlCanvasElement {
-  let window = web_sys::window().unwrap();
-  let document = window.document().unwrap();
-  let canvas = document
-      .get_element_by_id("snek_canvas")
-      .unwrap()
-      .dyn_into::<web_sys::HtmlCanvasElement>()
-      .unwrap();
-  let _ = canvas.focus();
-  canvas
+#[cfg(all(feature = "browser", target_arch = "wasm32"))]
+use crate::engine::browser::dom as platform;
+#[cfg(not(all(feature = "browser", target_arch = "wasm32")))]
+use native as platform;
+
+// print to the browser console, or stderr natively
+pub fn log(message: &str) {
+  platform::log(message);
+}
+
+// wall clock time in milliseconds since the unix epoch
+pub fn timestamp() -> f64 {
+  platform::timestamp()
 }
 
-pub fn request_animation_frame(callback: &Closure<dyn FnMut(f64)>) -> Result<i32,JsValue> {
-  web_sys::window().unwrap()
-    .request_animation_frame(callback.as_ref().unchecked_ref())
+// a string saved by this or an earlier session. kept in localStorage in the browser
+pub fn get_item(key: &str) -> Option<String> {
+  platform::get_item(key)
 }
 
-pub fn create_raf_closure(f: impl FnMut(f64) + 'static) -> Closure<dyn FnMut(f64)> {
-  Closure::wrap(Box::new(f))
+pub fn set_item(key: &str, value: &str) {
+  platform::set_item(key, value);
 }
 
-pub fn now() -> f64 {
-  web_sys::window().unwrap().performance().unwrap().now()
+pub fn remove_item(key: &str) {
+  platform::remove_item(key);
 }
 
+// natively there's nowhere to keep anything between sessions, so nothing is
+#[cfg(not(all(feature = "browser", target_arch = "wasm32")))]
+mod native {
+  use std::time::{SystemTime, UNIX_EPOCH};
+
+  pub fn log(message: &str) {
+    eprintln!("{message}");
+  }
+
+  pub fn timestamp() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as f64).unwrap_or(0.0)
+  }
+
+  pub fn get_item(_key: &str) -> Option<String> {
+    None
+  }
+
+  pub fn set_item(_key: &str, _value: &str) {}
+
+  pub fn remove_item(_key: &str) {}
+}
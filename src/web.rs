@@ -0,0 +1,219 @@
+// the javascript api, built with the browser backend
+use crate::engine::browser::dom;
+use crate::engine::{Action, Engine, EngineConfig, GameEvent, KeyBindings, Replay};
+use crate::snek;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(start)]
+pub fn start() {
+    console_error_panic_hook::set_once();
+}
+
+// create a game and start it, playing back the replay in the page url (?replay=...) if
+// there is one. this is what the bundled page does: `window.snek = snek.run()`
+#[wasm_bindgen]
+pub fn run(config: JsValue) -> Result<GameHandle, JsValue> {
+    let game = GameHandle::new(config)?;
+    let started = wasm_bindgen_futures::JsFuture::from(game.start(dom::query_param("replay"))?);
+    // run returns the game rather than the start promise, so a failed start is reported in
+    // the console instead
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(error) = started.await {
+            web_sys::console::error_1(&error);
+        }
+    });
+    Ok(game)
+}
+
+// a game embedded in a canvas on the host page. e.g.
+// ```
+// const game = new Game({ canvasId: "game" });
+// game.on("gameOver", event => console.log(event.won));
+// await game.start();
+// ```
+#[wasm_bindgen(js_name = Game)]
+pub struct GameHandle {
+    config: EngineConfig,
+    // the running engine, if the game has been started
+    engine: Rc<RefCell<Option<Engine>>>,
+    // bumped by every start and destroy, so a start that finishes after it's been replaced
+    // knows to stop its engine instead of keeping it
+    generation: Rc<Cell<u64>>,
+    // javascript callbacks and the names of the events they are subscribed to
+    callbacks: Rc<RefCell<Vec<(String, js_sys::Function)>>>,
+}
+
+#[wasm_bindgen(js_class = Game)]
+impl GameHandle {
+    // config is an optional object with any of the EngineConfig fields, e.g.
+    // `{ canvasId: "game", width: 640, height: 480, tickRate: 30, backgroundColor: "#111" }`
+    // plus `tuning`, overrides for the gameplay numbers in src/snek/tuning.json, and `seed`,
+    // the seed of the first run
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: JsValue) -> Result<GameHandle, JsValue> {
+        let config: EngineConfig = if config.is_undefined() || config.is_null() {
+            EngineConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config)?
+        };
+        config.validate()?;
+        Ok(Self {
+            config,
+            engine: Rc::new(RefCell::new(None)),
+            generation: Rc::new(Cell::new(0)),
+            callbacks: Rc::new(RefCell::new(Vec::new())),
+        })
+    }
+
+    // start a new run, replacing any run in progress. if a replay string is given, it is
+    // played back instead of reading the player's input. otherwise the run uses the seed in
+    // the config, or a random one. resolves once the game is running
+    pub fn start(&self, replay: Option<String>) -> Result<js_sys::Promise, JsValue> {
+        let playback = replay.map(|s| Replay::from_json(&s)).transpose()?;
+        self.destroy();
+        let (config, engine, callbacks) = (self.config.clone(), self.engine.clone(), self.callbacks.clone());
+        let (generation, current) = (self.generation.get(), self.generation.clone());
+        Ok(wasm_bindgen_futures::future_to_promise(async move {
+            let seed = playback.as_ref().map(Replay::seed).or(config.seed).unwrap_or_else(rand::random);
+            let game = snek::SnekApp::new(seed, &config, playback.is_some());
+            let started = Engine::start(game, config, playback).await?;
+            if current.get() != generation {
+                // start or destroy was called again while this one was starting
+                started.stop();
+                return Ok(JsValue::UNDEFINED);
+            }
+            started.subscribe(move |event| dispatch(&callbacks, event));
+            *engine.borrow_mut() = Some(started);
+            Ok(JsValue::UNDEFINED)
+        }))
+    }
+
+    // throw away the current run and start a new one, with the same seed if same_seed is
+    // true. the restart action does the same with a new seed
+    pub fn restart(&self, same_seed: Option<bool>) -> Result<(), JsValue> {
+        self.with_engine(|engine| engine.restart(same_seed.unwrap_or(false)))
+    }
+
+    // stop the game and release the canvas. start can be called again afterwards
+    pub fn destroy(&self) {
+        self.generation.set(self.generation.get() + 1);
+        if let Some(engine) = self.engine.borrow_mut().take() {
+            engine.stop();
+        }
+    }
+
+    // call callback with an event object whenever the named event happens: "scoreChanged",
+    // "gameOver" or "runRestored"
+    pub fn on(&self, event: String, callback: js_sys::Function) {
+        self.callbacks.borrow_mut().push((event, callback));
+    }
+
+    // remove a callback added with on
+    pub fn off(&self, event: &str, callback: &js_sys::Function) {
+        self.callbacks.borrow_mut().retain(|(e, c)| !(e == event && c == callback));
+    }
+
+    // stop updating the game until resume is called or the player presses pause, confirm
+    // or a direction (which includes a tap or swipe)
+    pub fn pause(&self) -> Result<(), JsValue> {
+        self.with_engine(Engine::pause)
+    }
+
+    pub fn resume(&self) -> Result<(), JsValue> {
+        self.with_engine(Engine::resume)
+    }
+
+    pub fn is_paused(&self) -> Result<bool, JsValue> {
+        self.with_engine(Engine::is_paused)
+    }
+
+    // the seed of the current run, as a BigInt
+    pub fn seed(&self) -> Result<u64, JsValue> {
+        self.with_engine(Engine::seed)
+    }
+
+    // the seed and inputs of the current run. loading the page with this string url-encoded
+    // as the `replay` query parameter plays the run back exactly. a run continued from a
+    // save can't be replayed
+    pub fn replay(&self) -> Result<String, JsValue> {
+        self.with_engine(|engine| engine.replay().map(|replay| replay.to_json()))?
+            .ok_or_else(|| JsValue::from("a continued run can't be replayed"))
+    }
+
+    // bind a KeyboardEvent.code (e.g. "KeyZ") to an action: "up", "down", "left", "right",
+    // "pause", "confirm", "back", "restart", "boost" or a game defined "game:<n>"
+    pub fn bind_key(&self, code: &str, action: &str) -> Result<(), JsValue> {
+        let action = Action::from_name(action).ok_or_else(|| JsValue::from(format!("unknown action: {action}")))?;
+        self.with_engine(|engine| {
+            let mut bindings = engine.key_bindings();
+            bindings.bind(code, action);
+            engine.set_key_bindings(bindings);
+        })
+    }
+
+    pub fn unbind_key(&self, code: &str) -> Result<(), JsValue> {
+        self.with_engine(|engine| {
+            let mut bindings = engine.key_bindings();
+            bindings.unbind(code);
+            engine.set_key_bindings(bindings);
+        })
+    }
+
+    // go back to arrow keys and WASD
+    pub fn reset_key_bindings(&self) -> Result<(), JsValue> {
+        self.with_engine(|engine| engine.set_key_bindings(KeyBindings::default()))
+    }
+
+    // the current bindings as a json object of code to action
+    pub fn key_bindings(&self) -> Result<String, JsValue> {
+        self.with_engine(|engine| engine.key_bindings().to_json())
+    }
+
+    // the saved high score table as a json array of { score, time, seed, date, name }, best
+    // first. time is in seconds and date in milliseconds since the unix epoch
+    pub fn high_scores(&self) -> String {
+        snek::HighScores::load().to_json()
+    }
+
+    // the name future high scores are recorded under. saved for future sessions
+    pub fn set_player_name(&self, name: &str) {
+        snek::set_player_name(name);
+    }
+
+    // frames, steps, capped frames, dropped seconds and dropped input events of the game
+    // loop as json
+    pub fn frame_stats(&self) -> Result<String, JsValue> {
+        self.with_engine(|engine| serde_json::to_string(&engine.frame_stats()).unwrap())
+    }
+
+    // limit how many simulation steps a single frame can run to catch up after a slow frame.
+    // must be at least 1
+    pub fn set_max_steps_per_frame(&self, max_steps_per_frame: u32) -> Result<(), JsValue> {
+        if max_steps_per_frame == 0 {
+            return Err(js_sys::Error::new("maxStepsPerFrame must be at least 1").into());
+        }
+        self.with_engine(|engine| engine.set_max_steps_per_frame(max_steps_per_frame))
+    }
+}
+
+impl GameHandle {
+    // run f with the running engine
+    fn with_engine<T>(&self, f: impl FnOnce(&Engine) -> T) -> Result<T, JsValue> {
+        self.engine.borrow().as_ref().map(f).ok_or_else(|| JsValue::from("the game is not running"))
+    }
+}
+
+// pass an event to the callbacks subscribed to it. the matching callbacks are collected
+// first so a callback can call on or off
+fn dispatch(callbacks: &RefCell<Vec<(String, js_sys::Function)>>, event: &GameEvent) {
+    let Ok(value) = serde_wasm_bindgen::to_value(event) else { return };
+    let subscribed: Vec<js_sys::Function> = callbacks.borrow().iter()
+        .filter(|(name, _)| name == event.name())
+        .map(|(_, callback)| callback.clone())
+        .collect();
+    for callback in subscribed {
+        let _ = callback.call1(&JsValue::NULL, &value);
+    }
+}
@@ -0,0 +1,41 @@
+// the simulation built natively, without the browser backend
+use snek::engine::{Action, EngineConfig, GameEvent, KeyState, RecordingRenderer};
+use snek::snek::SnekGame;
+
+fn pressing(actions: &[Action]) -> KeyState {
+  let mut key_state = KeyState::new();
+  for action in actions {
+    key_state.press(*action);
+  }
+  key_state
+}
+
+#[test]
+fn a_seed_and_inputs_determine_a_run() {
+  let config = EngineConfig::default();
+  let (mut a, mut b) = (SnekGame::new(42, &config), SnekGame::new(42, &config));
+  let inputs = [pressing(&[Action::Left]), KeyState::new(), pressing(&[Action::Up]), KeyState::new()];
+  for key_state in inputs.iter().cycle().take(400) {
+    a.update(key_state);
+    b.update(key_state);
+  }
+
+  let (frame_a, frame_b) = (RecordingRenderer::new(), RecordingRenderer::new());
+  a.draw(&frame_a, 1.0);
+  b.draw(&frame_b, 1.0);
+  assert_eq!(frame_a.take(), frame_b.take());
+  assert_eq!(a.score(), b.score());
+}
+
+#[test]
+fn running_into_the_boundary_ends_the_run() {
+  let mut game = SnekGame::new(0, &EngineConfig::default());
+  let down = pressing(&[Action::Down]);
+  let mut events = Vec::new();
+  while !game.is_over() {
+    game.update(&down);
+    events.extend(game.events());
+  }
+  assert!(!game.won());
+  assert_eq!(events.last(), Some(&GameEvent::GameOver { won: false }));
+}